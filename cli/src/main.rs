@@ -0,0 +1,681 @@
+use std::{error::Error, str::FromStr};
+
+use borsh::BorshDeserialize;
+use clap::{Arg, ArgMatches, Command};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use token_factory::{
+    builder::*, error::AppError, event::AppEvent, instruction, pda::*, state::*, utils::try_from_slice_unchecked,
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+struct Context {
+    client: RpcClient,
+    program_id: Pubkey,
+    payer: Keypair,
+    dry_run: bool,
+    simulate: bool,
+}
+
+fn main() {
+    let matches = app().get_matches();
+    if let Err(err) = run(&matches) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn app() -> Command<'static> {
+    let round = Arg::new("round")
+        .long("round")
+        .takes_value(true)
+        .help("Round number, the current round when omitted");
+    Command::new("token-factory-cli")
+        .about("Operates token_factory lottery rounds")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .global(true)
+                .takes_value(true)
+                .default_value("http://127.0.0.1:8899")
+                .help("JSON RPC endpoint"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .short('k')
+                .global(true)
+                .takes_value(true)
+                .help("Fee payer and default signer, ~/.config/solana/id.json when omitted"),
+        )
+        .arg(
+            Arg::new("program-id")
+                .long("program-id")
+                .global(true)
+                .takes_value(true)
+                .help("Program to talk to, the token_factory id when omitted"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .global(true)
+                .help("Print the instructions instead of sending them"),
+        )
+        .arg(
+            Arg::new("simulate")
+                .long("simulate")
+                .global(true)
+                .conflicts_with("dry-run")
+                .help("Simulate the transaction and print its logs and events"),
+        )
+        .subcommand(
+            Command::new("configure")
+                .about("Open the first round paying out a mint, signed by the upgrade authority")
+                .arg(Arg::new("mint").long("mint").takes_value(true).required(true))
+                .arg(Arg::new("round").long("round").takes_value(true).default_value("1"))
+                .arg(Arg::new("authority").long("authority").takes_value(true))
+                .arg(Arg::new("charge-addr").long("charge-addr").takes_value(true))
+                .arg(Arg::new("ticket-price").long("ticket-price").takes_value(true).required(true))
+                .arg(Arg::new("total-reward").long("total-reward").takes_value(true).default_value("0"))
+                .arg(Arg::new("start-time").long("start-time").takes_value(true).default_value("0"))
+                .arg(Arg::new("end-time").long("end-time").takes_value(true).default_value("0"))
+                .arg(Arg::new("max-batch").long("max-batch").takes_value(true).default_value("10"))
+                .arg(
+                    Arg::new("tiers")
+                        .long("tiers")
+                        .takes_value(true)
+                        .help("Six comma separated basis points, match1 to match6"),
+                )
+                .arg(
+                    Arg::new("randomness-source")
+                        .long("randomness-source")
+                        .takes_value(true)
+                        .possible_values(["commit-reveal", "slot-hashes", "oracle"])
+                        .default_value("slot-hashes"),
+                )
+                .arg(
+                    Arg::new("commitment")
+                        .long("commitment")
+                        .takes_value(true)
+                        .help("Hex keccak(secret) for commit-reveal rounds"),
+                )
+                .arg(Arg::new("oracle-program").long("oracle-program").takes_value(true))
+                .arg(Arg::new("oracle-authority").long("oracle-authority").takes_value(true))
+                .arg(Arg::new("payment-mint").long("payment-mint").takes_value(true))
+                .arg(Arg::new("crank-reward").long("crank-reward").takes_value(true).default_value("0"))
+                .arg(Arg::new("claim-period").long("claim-period").takes_value(true).default_value("0"))
+                .arg(
+                    Arg::new("revenue-share")
+                        .long("revenue-share")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .help("RECIPIENT:BP, repeat for up to four recipients"),
+                )
+                .arg(Arg::new("pot-bp").long("pot-bp").takes_value(true).default_value("0"))
+                .arg(Arg::new("referral-bp").long("referral-bp").takes_value(true).default_value("0"))
+                .arg(
+                    Arg::new("rollover")
+                        .long("rollover")
+                        .takes_value(true)
+                        .possible_values(["disabled", "all-tiers", "top-tier"])
+                        .default_value("disabled"),
+                ),
+        )
+        .subcommand(
+            Command::new("buy")
+                .about("Buy tickets in a round")
+                .arg(round.clone())
+                .arg(Arg::new("num").long("num").takes_value(true).default_value("1"))
+                .arg(
+                    Arg::new("shot")
+                        .long("shot")
+                        .takes_value(true)
+                        .help("Six digits, quick picks when omitted"),
+                )
+                .arg(Arg::new("referrer").long("referrer").takes_value(true))
+                .arg(
+                    Arg::new("buyer")
+                        .long("buyer")
+                        .takes_value(true)
+                        .help("Keypair buying the tickets, the fee payer when omitted"),
+                ),
+        )
+        .subcommand(
+            Command::new("close")
+                .about("Close a round and open the next one")
+                .arg(round.clone())
+                .arg(Arg::new("randomness").long("randomness").takes_value(true))
+                .arg(Arg::new("rollover-round").long("rollover-round").takes_value(true))
+                .arg(
+                    Arg::new("commitment")
+                        .long("commitment")
+                        .takes_value(true)
+                        .help("Hex keccak(secret) of the next round"),
+                ),
+        )
+        .subcommand(
+            Command::new("claim")
+                .about("Claim the reward of a settled round")
+                .arg(round.clone().required(true))
+                .arg(
+                    Arg::new("user")
+                        .long("user")
+                        .takes_value(true)
+                        .help("Keypair of the winner, the fee payer when omitted"),
+                )
+                .arg(Arg::new("token-account").long("token-account").takes_value(true)),
+        )
+        .subcommand(
+            Command::new("clear")
+                .about("Withdraw reward tokens from the vault to the authority")
+                .arg(round.clone())
+                .arg(Arg::new("amount").long("amount").takes_value(true).required(true))
+                .arg(Arg::new("token-account").long("token-account").takes_value(true)),
+        )
+        .subcommand(
+            Command::new("reveal")
+                .about("Reveal the secret of a commit-reveal round")
+                .arg(round.clone())
+                .arg(
+                    Arg::new("secret")
+                        .long("secret")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Hex secret the round committed to"),
+                ),
+        )
+        .subcommand(
+            Command::new("draw")
+                .about("Draw the target of a slot-hashes round, or of a commit-reveal round left unrevealed")
+                .arg(round.clone()),
+        )
+        .subcommand(
+            Command::new("consume-randomness")
+                .about("Read the target of an oracle round from its fulfilled request")
+                .arg(round.clone()),
+        )
+        .subcommand(
+            Command::new("settle")
+                .about("Check a user's unsettled ticket pages against the target")
+                .arg(round.clone())
+                .arg(
+                    Arg::new("user")
+                        .long("user")
+                        .takes_value(true)
+                        .help("Pubkey of the user, the fee payer when omitted"),
+                ),
+        )
+        .subcommand(
+            Command::new("withdraw-revenue")
+                .about("Withdraw a round's SPL ticket revenue to the authority")
+                .arg(round.clone())
+                .arg(Arg::new("amount").long("amount").takes_value(true).required(true))
+                .arg(Arg::new("token-account").long("token-account").takes_value(true)),
+        )
+        .subcommand(
+            Command::new("show-round")
+                .about("Print the config of a round")
+                .arg(round.clone()),
+        )
+        .subcommand(
+            Command::new("show-user")
+                .about("Print a user's entry and tickets in a round")
+                .arg(round)
+                .arg(
+                    Arg::new("user")
+                        .long("user")
+                        .takes_value(true)
+                        .help("Pubkey of the user, the fee payer when omitted"),
+                ),
+        )
+}
+
+fn run(matches: &ArgMatches) -> CliResult<()> {
+    let keypair = match matches.value_of("keypair") {
+        Some(path) => path.to_string(),
+        None => format!("{}/.config/solana/id.json", std::env::var("HOME")?),
+    };
+    let ctx = Context {
+        client: RpcClient::new_with_commitment(
+            matches.value_of("url").unwrap().to_string(),
+            CommitmentConfig::confirmed(),
+        ),
+        program_id: pubkey_of(matches, "program-id")?.unwrap_or_else(token_factory::id),
+        payer: read_keypair(&keypair)?,
+        dry_run: matches.is_present("dry-run"),
+        simulate: matches.is_present("simulate"),
+    };
+
+    match matches.subcommand() {
+        Some(("configure", args)) => configure(&ctx, args),
+        Some(("buy", args)) => buy(&ctx, args),
+        Some(("close", args)) => close(&ctx, args),
+        Some(("claim", args)) => claim(&ctx, args),
+        Some(("clear", args)) => clear(&ctx, args),
+        Some(("reveal", args)) => reveal(&ctx, args),
+        Some(("draw", args)) => draw(&ctx, args),
+        Some(("consume-randomness", args)) => consume_randomness(&ctx, args),
+        Some(("settle", args)) => settle(&ctx, args),
+        Some(("withdraw-revenue", args)) => withdraw_revenue(&ctx, args),
+        Some(("show-round", args)) => show_round(&ctx, args),
+        Some(("show-user", args)) => show_user(&ctx, args),
+        _ => unreachable!(),
+    }
+}
+
+fn configure(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let payer = ctx.payer.pubkey();
+    let mint = pubkey_of(args, "mint")?.unwrap();
+    let mut revenue_shares = [RevenueShare::default(); MAX_REVENUE_RECIPIENTS];
+    for (i, share) in args.values_of("revenue-share").into_iter().flatten().enumerate() {
+        if i == MAX_REVENUE_RECIPIENTS {
+            return Err(format!("at most {} revenue shares", MAX_REVENUE_RECIPIENTS).into());
+        }
+        let (recipient, bp) = share.split_once(':').ok_or("revenue share must be RECIPIENT:BP")?;
+        revenue_shares[i] = RevenueShare {
+            recipient: Pubkey::from_str(recipient)?,
+            bp: bp.parse()?,
+        };
+    }
+    let tiers = match args.value_of("tiers") {
+        Some(tiers) => tiers
+            .split(',')
+            .map(|bp| bp.trim().parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .map_err(|_| "tiers takes six values")?,
+        None => DEFAULT_TIERS,
+    };
+    let configure_args = ConfigureArgs {
+        authority: pubkey_of(args, "authority")?.unwrap_or(payer),
+        charge_addr: pubkey_of(args, "charge-addr")?.unwrap_or(payer),
+        round: args.value_of("round").unwrap().to_string(),
+        start_time: parse_of(args, "start-time")?,
+        total_reward: parse_of(args, "total-reward")?,
+        commitment: hash_of(args, "commitment")?.unwrap_or_default(),
+        randomness_source: match args.value_of("randomness-source").unwrap() {
+            "commit-reveal" => RandomnessSource::CommitReveal,
+            "slot-hashes" => RandomnessSource::SlotHashes,
+            _ => RandomnessSource::Oracle,
+        },
+        oracle_program: pubkey_of(args, "oracle-program")?.unwrap_or_default(),
+        max_batch: parse_of(args, "max-batch")?,
+        tiers,
+        ticket_price: parse_of(args, "ticket-price")?,
+        payment_mint: pubkey_of(args, "payment-mint")?,
+        oracle_authority: pubkey_of(args, "oracle-authority")?.unwrap_or_default(),
+        end_time: parse_of(args, "end-time")?,
+        crank_reward: parse_of(args, "crank-reward")?,
+        claim_period: parse_of(args, "claim-period")?,
+        revenue_shares,
+        pot_bp: parse_of(args, "pot-bp")?,
+        rollover_mode: match args.value_of("rollover").unwrap() {
+            "all-tiers" => RolloverMode::AllTiers,
+            "top-tier" => RolloverMode::TopTier,
+            _ => RolloverMode::Disabled,
+        },
+        referral_bp: parse_of(args, "referral-bp")?,
+    };
+    let instruction = InitializeRoundBuilder::new(&ctx.program_id, &payer, &mint, configure_args).instruction()?;
+    send(ctx, &[instruction], &[])
+}
+
+fn buy(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let buyer = match args.value_of("buyer") {
+        Some(path) => read_keypair(path)?,
+        None => Keypair::from_bytes(&ctx.payer.to_bytes())?,
+    };
+    let round = round_of(ctx, args)?;
+    let config_data: ConfigureData = load(ctx, &find_config_address(&ctx.program_id, round).0)?;
+    let user_info = find_user_info_address(&ctx.program_id, &buyer.pubkey(), round).0;
+    let user_data: Option<UserData> = try_load(ctx, &user_info)?;
+    let shot = match args.value_of("shot") {
+        Some(shot) => {
+            let digits = shot
+                .chars()
+                .map(|c| c.to_digit(10).map(|d| d as u8))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("shot must be six digits")?;
+            Some(digits.try_into().map_err(|_| "shot must be six digits")?)
+        }
+        None => None,
+    };
+    let buy_args = BuyTicketsArgs {
+        shot,
+        num: parse_of(args, "num")?,
+        referrer: pubkey_of(args, "referrer")?,
+    };
+    let instruction =
+        BuyBuilder::new(&ctx.program_id, &buyer.pubkey(), &config_data, user_data.as_ref(), buy_args).instruction()?;
+    send(ctx, &[instruction], &[&buyer])
+}
+
+fn close(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let round = round_of(ctx, args)?;
+    let config_data: ConfigureData = load(ctx, &find_config_address(&ctx.program_id, round).0)?;
+    let mut builder = CloseBuilder::new(&ctx.program_id, &ctx.payer.pubkey(), &config_data.token, round);
+    if let Some(randomness) = pubkey_of(args, "randomness")? {
+        builder = builder.randomness(randomness);
+    }
+    if args.is_present("rollover-round") {
+        builder = builder.rollover_round(parse_of(args, "rollover-round")?);
+    }
+    if let Some(commitment) = hash_of(args, "commitment")? {
+        builder = builder.commitment(commitment);
+    }
+    send(ctx, &[builder.instruction()?], &[])
+}
+
+fn claim(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let user = match args.value_of("user") {
+        Some(path) => read_keypair(path)?,
+        None => Keypair::from_bytes(&ctx.payer.to_bytes())?,
+    };
+    let round = parse_of(args, "round")?;
+    let config_data: ConfigureData = load(ctx, &find_config_address(&ctx.program_id, round).0)?;
+    let mut instructions = vec![];
+    let mut builder = ClaimBuilder::new(&ctx.program_id, &user.pubkey(), &config_data.token, round)
+        .pot_mode(config_data.is_pot_mode());
+    match pubkey_of(args, "token-account")? {
+        Some(token_account) => builder = builder.token_account(token_account),
+        None => instructions.push(create_associated_token_account_idempotent(
+            &ctx.payer.pubkey(),
+            &user.pubkey(),
+            &config_data.token,
+            &spl_token::id(),
+        )),
+    }
+    instructions.push(builder.instruction()?);
+    send(ctx, &instructions, &[&user])
+}
+
+fn clear(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let authority = ctx.payer.pubkey();
+    let round = round_of(ctx, args)?;
+    let config_data: ConfigureData = load(ctx, &find_config_address(&ctx.program_id, round).0)?;
+    let mut instructions = vec![];
+    let mut builder = ClearBuilder::new(
+        &ctx.program_id,
+        &authority,
+        &config_data.token,
+        round,
+        parse_of(args, "amount")?,
+    );
+    match pubkey_of(args, "token-account")? {
+        Some(token_account) => builder = builder.token_account(token_account),
+        None => instructions.push(create_associated_token_account_idempotent(
+            &authority,
+            &authority,
+            &config_data.token,
+            &spl_token::id(),
+        )),
+    }
+    instructions.push(builder.instruction()?);
+    send(ctx, &instructions, &[])
+}
+
+fn reveal(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let config = find_config_address(&ctx.program_id, round_of(ctx, args)?).0;
+    let reveal_args = RevealTargetArgs {
+        secret: hash_of(args, "secret")?.unwrap(),
+    };
+    let instruction = instruction::reveal_target(&ctx.program_id, &ctx.payer.pubkey(), &config, reveal_args)?;
+    send(ctx, &[instruction], &[])
+}
+
+fn draw(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let config = find_config_address(&ctx.program_id, round_of(ctx, args)?).0;
+    send(ctx, &[instruction::draw(&ctx.program_id, &config)?], &[])
+}
+
+fn consume_randomness(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let config = find_config_address(&ctx.program_id, round_of(ctx, args)?).0;
+    let config_data: ConfigureData = load(ctx, &config)?;
+    let instruction = instruction::consume_randomness(&ctx.program_id, &config, &config_data.randomness_account)?;
+    send(ctx, &[instruction], &[])
+}
+
+/// Sends one transaction per unsettled page, a page can take most of a transaction's compute.
+fn settle(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let user = pubkey_of(args, "user")?.unwrap_or_else(|| ctx.payer.pubkey());
+    let round = round_of(ctx, args)?;
+    let config = find_config_address(&ctx.program_id, round).0;
+    let config_data: ConfigureData = load(ctx, &config)?;
+    let user_info = find_user_info_address(&ctx.program_id, &user, round).0;
+    let user_data: UserData = load(ctx, &user_info)?;
+    for page in 0..user_data.pages {
+        let address = find_ticket_page_address(&ctx.program_id, &user, round, page).0;
+        let page_data: TicketPage = load(ctx, &address)?;
+        if page_data.settled {
+            continue;
+        }
+        println!("page {}", page);
+        let settle = instruction::settle(&ctx.program_id, &config, &config_data.token, &user, &user_info, &address)?;
+        send(ctx, &[settle], &[])?;
+    }
+    Ok(())
+}
+
+fn withdraw_revenue(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let authority = ctx.payer.pubkey();
+    let config = find_config_address(&ctx.program_id, round_of(ctx, args)?).0;
+    let config_data: ConfigureData = load(ctx, &config)?;
+    let payment_mint = config_data.payment_mint.ok_or("the round is paid in SOL")?;
+    let mut instructions = vec![];
+    let token_account = match pubkey_of(args, "token-account")? {
+        Some(token_account) => token_account,
+        None => {
+            instructions.push(create_associated_token_account_idempotent(
+                &authority,
+                &authority,
+                &payment_mint,
+                &spl_token::id(),
+            ));
+            get_associated_token_address(&authority, &payment_mint)
+        }
+    };
+    instructions.push(instruction::withdraw_revenue(
+        &ctx.program_id,
+        &authority,
+        &config,
+        &payment_mint,
+        &token_account,
+        WithdrawRevenueArgs {
+            amount: parse_of(args, "amount")?,
+        },
+    )?);
+    send(ctx, &instructions, &[])
+}
+
+fn show_round(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let config = find_config_address(&ctx.program_id, round_of(ctx, args)?).0;
+    let config_data: ConfigureData = load(ctx, &config)?;
+    println!("config: {}", config);
+    println!("{:#?}", config_data);
+    Ok(())
+}
+
+fn show_user(ctx: &Context, args: &ArgMatches) -> CliResult<()> {
+    let user = pubkey_of(args, "user")?.unwrap_or_else(|| ctx.payer.pubkey());
+    let round = round_of(ctx, args)?;
+    let user_info = find_user_info_address(&ctx.program_id, &user, round).0;
+    let user_data: UserData = load(ctx, &user_info)?;
+    println!("user_info: {}", user_info);
+    println!("{:#?}", user_data);
+    for page in 0..user_data.pages {
+        let address = find_ticket_page_address(&ctx.program_id, &user, round, page).0;
+        let page_data: TicketPage = load(ctx, &address)?;
+        println!("page {} ({}), settled: {}", page, address, page_data.settled);
+        for TicketEntry { shot, num } in page_data.tickets {
+            let shot: String = shot.iter().map(|digit| digit.to_string()).collect();
+            println!("  {} x{}", shot, num);
+        }
+    }
+    Ok(())
+}
+
+/// Signs with the fee payer and `signers`, then prints, simulates or sends.
+fn send(ctx: &Context, instructions: &[Instruction], signers: &[&Keypair]) -> CliResult<()> {
+    if ctx.dry_run {
+        for instruction in instructions {
+            println!("program {}", instruction.program_id);
+            for meta in &instruction.accounts {
+                let signer = if meta.is_signer { "signer" } else { "" };
+                let writable = if meta.is_writable { "writable" } else { "" };
+                println!("  {} {} {}", meta.pubkey, writable, signer);
+            }
+            println!("  data {} bytes", instruction.data.len());
+        }
+        return Ok(());
+    }
+
+    let mut all_signers = vec![&ctx.payer];
+    for signer in signers {
+        if !all_signers.iter().any(|known| known.pubkey() == signer.pubkey()) {
+            all_signers.push(signer);
+        }
+    }
+    let blockhash = ctx.client.get_latest_blockhash()?;
+    let transaction =
+        Transaction::new_signed_with_payer(instructions, Some(&ctx.payer.pubkey()), &all_signers, blockhash);
+
+    if ctx.simulate {
+        let result = ctx.client.simulate_transaction(&transaction)?.value;
+        let logs = result.logs.unwrap_or_default();
+        for log in &logs {
+            println!("{}", log);
+        }
+        print_events(ctx, &logs);
+        return match result.err {
+            Some(err) => Err(describe_transaction_error(&err).into()),
+            None => Ok(()),
+        };
+    }
+
+    match ctx.client.send_and_confirm_transaction(&transaction) {
+        Ok(signature) => {
+            println!("signature: {}", signature);
+            Ok(())
+        }
+        Err(err) => Err(describe_client_error(err).into()),
+    }
+}
+
+fn print_events(ctx: &Context, logs: &[String]) {
+    for event in AppEvent::from_logs(&ctx.program_id, logs) {
+        println!("{:#?}", event);
+    }
+}
+
+fn describe_client_error(err: ClientError) -> String {
+    match err.get_transaction_error() {
+        Some(transaction_error) => describe_transaction_error(&transaction_error),
+        None => err.to_string(),
+    }
+}
+
+fn describe_transaction_error(err: &TransactionError) -> String {
+    if let TransactionError::InstructionError(index, instruction_error) = err {
+        if let Some(app_error) = AppError::from_instruction_error(instruction_error) {
+            return format!("instruction {}: {:?}: {}", index, app_error, app_error);
+        }
+    }
+    err.to_string()
+}
+
+fn read_keypair(path: &str) -> CliResult<Keypair> {
+    read_keypair_file(path).map_err(|err| format!("cannot read keypair {}: {}", path, err).into())
+}
+
+fn load<T: BorshDeserialize>(ctx: &Context, address: &Pubkey) -> CliResult<T> {
+    try_load(ctx, address)?.ok_or_else(|| format!("account {} not found", address).into())
+}
+
+fn try_load<T: BorshDeserialize>(ctx: &Context, address: &Pubkey) -> CliResult<Option<T>> {
+    let account = ctx
+        .client
+        .get_account_with_commitment(address, CommitmentConfig::confirmed())?
+        .value;
+    match account {
+        Some(account) => Ok(Some(try_from_slice_unchecked(&account.data)?)),
+        None => Ok(None),
+    }
+}
+
+/// The round given with --round, or the one the round account points at.
+fn round_of(ctx: &Context, args: &ArgMatches) -> CliResult<u64> {
+    if args.is_present("round") {
+        return parse_of(args, "round");
+    }
+    let round_data: RoundData = load(ctx, &find_round_address(&ctx.program_id).0)?;
+    Ok(round_data.round)
+}
+
+fn pubkey_of(args: &ArgMatches, name: &str) -> CliResult<Option<Pubkey>> {
+    match args.value_of(name) {
+        Some(value) => Ok(Some(
+            Pubkey::from_str(value).map_err(|err| format!("--{}: {}", name, err))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+fn parse_of<T: FromStr>(args: &ArgMatches, name: &str) -> CliResult<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = args.value_of(name).ok_or_else(|| format!("--{} is required", name))?;
+    value
+        .parse()
+        .map_err(|err| format!("--{}: {}", name, err).into())
+}
+
+fn hash_of(args: &ArgMatches, name: &str) -> CliResult<Option<[u8; 32]>> {
+    let value = match args.value_of(name) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    // from_str_radix takes a leading sign, and byte offsets only land on char boundaries for ascii
+    if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("--{} takes 32 hex encoded bytes", name).into());
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[2 * i..2 * i + 2], 16)
+            .map_err(|err| format!("--{}: {}", name, err))?;
+    }
+    Ok(Some(hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_of(secret: &str) -> CliResult<Option<[u8; 32]>> {
+        let matches = app().get_matches_from(["token-factory-cli", "reveal", "--secret", secret]);
+        let (_, args) = matches.subcommand().unwrap();
+        hash_of(args, "secret")
+    }
+
+    #[test]
+    fn test_hash_of() {
+        assert_eq!(secret_of(&"ab".repeat(32)).unwrap(), Some([0xab; 32]));
+        assert!(secret_of(&"ab".repeat(31)).is_err());
+        // 64 bytes, but a two byte char straddles the first hex pair
+        assert!(secret_of(&format!("a{}a", "é".repeat(31))).is_err());
+        // a signed pair parses on its own, but is not hex
+        assert!(secret_of(&format!("+a{}", "ab".repeat(31))).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub enum OracleInstruction {
+    /// Creates a pending randomness account that only `authority` can fulfill
+    Request { authority: Pubkey },
+    /// Writes the value and marks the request fulfilled
+    Fulfill { value: [u8; 32] },
+}
+
+pub fn request(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    randomness_info: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*randomness_info, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: OracleInstruction::Request { authority: *authority }.try_to_vec().unwrap(),
+    })
+}
+
+pub fn fulfill(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    randomness_info: &Pubkey,
+    value: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*randomness_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: OracleInstruction::Fulfill { value }.try_to_vec().unwrap(),
+    })
+}
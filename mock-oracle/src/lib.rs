@@ -0,0 +1,6 @@
+#[cfg(not(feature = "no-entrypoint"))]
+pub mod entrypoint;
+pub mod instruction;
+pub mod processor;
+
+solana_program::declare_id!("6sDJuYfZz692ciRJrXeRWVx6AC5sJSnX9zCE61k3JAq8");
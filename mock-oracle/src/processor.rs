@@ -0,0 +1,76 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use oracle_interface::OracleRandomness;
+
+use crate::instruction::OracleInstruction;
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction = OracleInstruction::try_from_slice(input)?;
+    let account_info_iter = &mut accounts.iter();
+    match instruction {
+        OracleInstruction::Request { authority } => {
+            msg!("Instruction: Request");
+            let payer_info = next_account_info(account_info_iter)?;
+            let randomness_info = next_account_info(account_info_iter)?;
+            let system_info = next_account_info(account_info_iter)?;
+
+            if !payer_info.is_signer || !randomness_info.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            invoke(
+                &system_instruction::create_account(
+                    payer_info.key,
+                    randomness_info.key,
+                    Rent::get()?.minimum_balance(OracleRandomness::LEN),
+                    OracleRandomness::LEN as u64,
+                    program_id,
+                ),
+                &[payer_info.clone(), randomness_info.clone(), system_info.clone()],
+            )?;
+
+            let randomness = OracleRandomness {
+                authority,
+                ..Default::default()
+            };
+            randomness.serialize(&mut &mut randomness_info.data.borrow_mut()[..])?;
+        }
+        OracleInstruction::Fulfill { value } => {
+            msg!("Instruction: Fulfill");
+            let authority_info = next_account_info(account_info_iter)?;
+            let randomness_info = next_account_info(account_info_iter)?;
+
+            if !authority_info.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if randomness_info.owner != program_id {
+                return Err(ProgramError::IllegalOwner);
+            }
+            let mut randomness = OracleRandomness::from_account_info(randomness_info)?;
+            if *authority_info.key != randomness.authority {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if randomness.fulfilled {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+
+            randomness.fulfilled = true;
+            randomness.value = value;
+            randomness.serialize(&mut &mut randomness_info.data.borrow_mut()[..])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+/// Randomness account layout expected from the oracle program.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct OracleRandomness {
+    /// oracle key allowed to fulfill the request
+    pub authority: Pubkey,
+    pub fulfilled: bool,
+    pub value: [u8; 32],
+}
+
+impl OracleRandomness {
+    pub const LEN: usize = 32 + 1 + 32;
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<OracleRandomness, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        OracleRandomness::deserialize(&mut &a.data.borrow()[..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
@@ -0,0 +1,360 @@
+//! Builders that resolve the accounts of an instruction from the few keys a client knows.
+//!
+//! Every derived account can still be replaced through its setter.
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{instruction::*, pda::*, state::*};
+
+/// Opens a round paying out `mint`.
+#[derive(Clone)]
+pub struct InitializeRoundBuilder {
+    program_id: Pubkey,
+    authority: Pubkey,
+    mint: Pubkey,
+    config: Option<Pubkey>,
+    latest_round: Option<u64>,
+    args: ConfigureArgs,
+}
+
+impl InitializeRoundBuilder {
+    pub fn new(program_id: &Pubkey, authority: &Pubkey, mint: &Pubkey, args: ConfigureArgs) -> Self {
+        Self {
+            program_id: *program_id,
+            authority: *authority,
+            mint: *mint,
+            config: None,
+            latest_round: None,
+            args,
+        }
+    }
+
+    pub fn config(mut self, config: Pubkey) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Round held by RoundData, which has to be closed first; the one before `args.round` by default.
+    pub fn latest_round(mut self, latest_round: u64) -> Self {
+        self.latest_round = Some(latest_round);
+        self
+    }
+
+    pub fn instruction(self) -> Result<Instruction, ProgramError> {
+        let round: u64 = self.args.round.parse().map_err(|_| ProgramError::InvalidArgument)?;
+        let config = self
+            .config
+            .unwrap_or_else(|| find_config_address(&self.program_id, round).0);
+        let latest_round = self.latest_round.unwrap_or(round.saturating_sub(1));
+        let payment_mint = self.args.payment_mint;
+        initialize_round(
+            &self.program_id,
+            &self.authority,
+            &config,
+            &find_config_address(&self.program_id, latest_round).0,
+            &self.mint,
+            payment_mint.as_ref(),
+            self.args,
+        )
+    }
+}
+
+/// Buys tickets in the round described by `config`.
+#[derive(Clone)]
+pub struct BuyBuilder {
+    program_id: Pubkey,
+    user: Pubkey,
+    config_data: ConfigureData,
+    config: Option<Pubkey>,
+    user_info: Option<Pubkey>,
+    charge: Option<Pubkey>,
+    page: u64,
+    token_account: Option<Pubkey>,
+    referrer_account: Option<Pubkey>,
+    args: BuyTicketsArgs,
+}
+
+impl BuyBuilder {
+    /// `user_data` is the user's entry in the round, None before its first buy; new tickets go to its last page.
+    pub fn new(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        config_data: &ConfigureData,
+        user_data: Option<&UserData>,
+        args: BuyTicketsArgs,
+    ) -> Self {
+        Self {
+            program_id: *program_id,
+            user: *user,
+            config_data: config_data.clone(),
+            config: None,
+            user_info: None,
+            charge: None,
+            page: user_data.map_or(0, |user_data| user_data.pages.saturating_sub(1)),
+            token_account: None,
+            referrer_account: None,
+            args,
+        }
+    }
+
+    pub fn config(mut self, config: Pubkey) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn user_info(mut self, user_info: Pubkey) -> Self {
+        self.user_info = Some(user_info);
+        self
+    }
+
+    pub fn charge(mut self, charge: Pubkey) -> Self {
+        self.charge = Some(charge);
+        self
+    }
+
+    /// Token account paying for the tickets, the user's ATA by default.
+    pub fn token_account(mut self, token_account: Pubkey) -> Self {
+        self.token_account = Some(token_account);
+        self
+    }
+
+    /// Account receiving the referral share, the referrer or its ATA by default.
+    pub fn referrer_account(mut self, referrer_account: Pubkey) -> Self {
+        self.referrer_account = Some(referrer_account);
+        self
+    }
+
+    pub fn instruction(self) -> Result<Instruction, ProgramError> {
+        let program_id = &self.program_id;
+        let round = self.config_data.round;
+        let payment_mint = self.config_data.payment_mint;
+        let recipients: Vec<Pubkey> = self
+            .config_data
+            .active_revenue_shares()
+            .map(|share| share.recipient)
+            .collect();
+        let referrer_account = self.args.referrer.map(|referrer| {
+            let account = self.referrer_account.unwrap_or_else(|| match &payment_mint {
+                Some(payment_mint) => get_associated_token_address(&referrer, payment_mint),
+                None => referrer,
+            });
+            (account, find_referrer_stats_address(program_id, &referrer).0)
+        });
+        let payment_accounts = payment_mint.map(|payment_mint| {
+            (
+                self.token_account
+                    .unwrap_or_else(|| get_associated_token_address(&self.user, &payment_mint)),
+                find_revenue_vault_address(program_id, &payment_mint).0,
+            )
+        });
+        buy(
+            program_id,
+            &self.user,
+            &self.config.unwrap_or_else(|| find_config_address(program_id, round).0),
+            &self
+                .user_info
+                .unwrap_or_else(|| find_user_info_address(program_id, &self.user, round).0),
+            &self.charge.unwrap_or(self.config_data.charge_addr),
+            &find_ticket_page_address(program_id, &self.user, round, self.page).0,
+            &find_ticket_page_address(program_id, &self.user, round, self.page + 1).0,
+            &recipients,
+            self.config_data.is_pot_mode(),
+            referrer_account.as_ref().map(|(account, stats)| (account, stats)),
+            payment_accounts.as_ref().map(|(token_account, vault)| (token_account, vault)),
+            self.args,
+        )
+    }
+}
+
+/// Closes `round` of `mint` and opens the one after it.
+#[derive(Clone)]
+pub struct CloseBuilder {
+    program_id: Pubkey,
+    signer: Pubkey,
+    mint: Pubkey,
+    round: u64,
+    config: Option<Pubkey>,
+    new_config: Option<Pubkey>,
+    randomness: Option<Pubkey>,
+    rollover_config: Option<Pubkey>,
+    args: CloseRoundArgs,
+}
+
+impl CloseBuilder {
+    pub fn new(program_id: &Pubkey, signer: &Pubkey, mint: &Pubkey, round: u64) -> Self {
+        Self {
+            program_id: *program_id,
+            signer: *signer,
+            mint: *mint,
+            round,
+            config: None,
+            new_config: None,
+            randomness: None,
+            rollover_config: None,
+            args: CloseRoundArgs::default(),
+        }
+    }
+
+    pub fn config(mut self, config: Pubkey) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn new_config(mut self, new_config: Pubkey) -> Self {
+        self.new_config = Some(new_config);
+        self
+    }
+
+    /// Pending oracle request bound to the round, for the Oracle randomness source.
+    pub fn randomness(mut self, randomness: Pubkey) -> Self {
+        self.randomness = Some(randomness);
+        self
+    }
+
+    /// Earlier settled round whose unallocated rewards move into the new round, at most once per round.
+    pub fn rollover_round(mut self, round: u64) -> Self {
+        self.rollover_config = Some(find_config_address(&self.program_id, round).0);
+        self
+    }
+
+    /// Commitment of the new round, for the CommitReveal randomness source.
+    pub fn commitment(mut self, commitment: [u8; 32]) -> Self {
+        self.args.commitment = commitment;
+        self
+    }
+
+    pub fn instruction(self) -> Result<Instruction, ProgramError> {
+        let program_id = &self.program_id;
+        close(
+            program_id,
+            &self.signer,
+            &self.config.unwrap_or_else(|| find_config_address(program_id, self.round).0),
+            &self
+                .new_config
+                .unwrap_or_else(|| find_config_address(program_id, self.round + 1).0),
+            &self.mint,
+            self.randomness.as_ref(),
+            self.rollover_config.as_ref(),
+            self.args,
+        )
+    }
+}
+
+/// Claims the reward of `user` in `round`.
+#[derive(Clone)]
+pub struct ClaimBuilder {
+    program_id: Pubkey,
+    user: Pubkey,
+    mint: Pubkey,
+    round: u64,
+    config: Option<Pubkey>,
+    user_info: Option<Pubkey>,
+    token_account: Option<Pubkey>,
+    pot_mode: bool,
+}
+
+impl ClaimBuilder {
+    pub fn new(program_id: &Pubkey, user: &Pubkey, mint: &Pubkey, round: u64) -> Self {
+        Self {
+            program_id: *program_id,
+            user: *user,
+            mint: *mint,
+            round,
+            config: None,
+            user_info: None,
+            token_account: None,
+            pot_mode: false,
+        }
+    }
+
+    pub fn config(mut self, config: Pubkey) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn user_info(mut self, user_info: Pubkey) -> Self {
+        self.user_info = Some(user_info);
+        self
+    }
+
+    /// Token account receiving the reward, the user's ATA by default.
+    pub fn token_account(mut self, token_account: Pubkey) -> Self {
+        self.token_account = Some(token_account);
+        self
+    }
+
+    /// Pays the reward in lamports from the prize pot.
+    pub fn pot_mode(mut self, pot_mode: bool) -> Self {
+        self.pot_mode = pot_mode;
+        self
+    }
+
+    pub fn instruction(self) -> Result<Instruction, ProgramError> {
+        let program_id = &self.program_id;
+        claim(
+            program_id,
+            &self.user,
+            &self.config.unwrap_or_else(|| find_config_address(program_id, self.round).0),
+            &self.mint,
+            &self
+                .user_info
+                .unwrap_or_else(|| find_user_info_address(program_id, &self.user, self.round).0),
+            &self
+                .token_account
+                .unwrap_or_else(|| get_associated_token_address(&self.user, &self.mint)),
+            self.pot_mode,
+            ClaimArgs { round: self.round },
+        )
+    }
+}
+
+/// Withdraws `amount` of `mint` from the vault to the round authority.
+#[derive(Clone)]
+pub struct ClearBuilder {
+    program_id: Pubkey,
+    authority: Pubkey,
+    mint: Pubkey,
+    round: u64,
+    amount: u64,
+    config: Option<Pubkey>,
+    token_account: Option<Pubkey>,
+}
+
+impl ClearBuilder {
+    pub fn new(program_id: &Pubkey, authority: &Pubkey, mint: &Pubkey, round: u64, amount: u64) -> Self {
+        Self {
+            program_id: *program_id,
+            authority: *authority,
+            mint: *mint,
+            round,
+            amount,
+            config: None,
+            token_account: None,
+        }
+    }
+
+    pub fn config(mut self, config: Pubkey) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Token account receiving the tokens, the authority's ATA by default.
+    pub fn token_account(mut self, token_account: Pubkey) -> Self {
+        self.token_account = Some(token_account);
+        self
+    }
+
+    pub fn instruction(self) -> Result<Instruction, ProgramError> {
+        let program_id = &self.program_id;
+        clear(
+            program_id,
+            &self.authority,
+            &self.config.unwrap_or_else(|| find_config_address(program_id, self.round).0),
+            &self.mint,
+            &self
+                .token_account
+                .unwrap_or_else(|| get_associated_token_address(&self.authority, &self.mint)),
+            ClearArgs { amt: self.amount },
+        )
+    }
+}
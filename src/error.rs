@@ -1,15 +1,14 @@
-use solana_program::program_error::ProgramError;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    instruction::InstructionError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
 use thiserror::Error;
 
-#[macro_export]
-macro_rules! ferror {
-    ($s:expr) => {{
-        msg!("ferror {}", $s);
-        Err(ProgramError::Custom(0xfe as u32))
-    }};
-}
-
-#[derive(Error, Debug, Copy, Clone)]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
 pub enum AppError {
     #[error("Invalid signer")]
     InvalidSigner = 0xfa01,
@@ -41,14 +40,188 @@ pub enum AppError {
     #[error("Checked calculate failed")]
     CheckedCalculateFailed = 0xfa0a,
 
-    #[error("Invalid eq pubkey")]
+    #[error("Invalid eq pubkey (0)")]
     InvalidEqPubkey0 = 0xfa18,
 
-    #[error("Invalid eq pubkey")]
+    #[error("Invalid eq pubkey (1)")]
     InvalidEqPubkey1 = 0xfa19,
 
-    #[error("Invalid eq pubkey")]
+    #[error("Invalid eq pubkey (2)")]
     InvalidEqPubkey2 = 0xfa1a,
+
+    #[error("Buys paused")]
+    BuysPaused = 0xfa1b,
+
+    #[error("Claims paused")]
+    ClaimsPaused = 0xfa1c,
+
+    #[error("Invalid account version")]
+    InvalidAccountVersion = 0xfa1d,
+
+    #[error("Sale not open")]
+    SaleNotOpen = 0xfa20,
+
+    #[error("Sale ended")]
+    SaleEnded = 0xfa21,
+
+    #[error("Sale not closed")]
+    SaleNotClosed = 0xfa22,
+
+    #[error("Round closed")]
+    RoundClosed = 0xfa23,
+
+    #[error("Invalid authority")]
+    InvalidAuthority = 0xfa24,
+
+    #[error("Invalid pending authority")]
+    InvalidPendingAuthority = 0xfa25,
+
+    #[error("No pending authority")]
+    NoPendingAuthority = 0xfa26,
+
+    #[error("Invalid guardian")]
+    InvalidGuardian = 0xfa27,
+
+    #[error("Invalid max batch")]
+    InvalidMaxBatch = 0xfa28,
+
+    #[error("Invalid ticket num")]
+    InvalidTicketNum = 0xfa29,
+
+    #[error("Invalid ticket price")]
+    InvalidTicketPrice = 0xfa2a,
+
+    #[error("Invalid shot")]
+    InvalidShot = 0xfa2b,
+
+    #[error("Invalid tiers")]
+    InvalidTiers = 0xfa2c,
+
+    #[error("Invalid revenue shares")]
+    InvalidRevenueShares = 0xfa2d,
+
+    #[error("Invalid end time")]
+    InvalidEndTime = 0xfa2e,
+
+    #[error("Invalid total reward")]
+    InvalidTotalReward = 0xfa2f,
+
+    #[error("Prize pot requires SOL payments")]
+    PotRequiresSolPayments = 0xfa30,
+
+    #[error("Tickets already sold")]
+    TicketsSold = 0xfa31,
+
+    #[error("Commitment not set")]
+    CommitmentNotSet = 0xfa32,
+
+    #[error("Commitment already set")]
+    CommitmentAlreadySet = 0xfa33,
+
+    #[error("Invalid secret")]
+    InvalidSecret = 0xfa34,
+
+    #[error("Invalid randomness source")]
+    InvalidRandomnessSource = 0xfa35,
+
+    #[error("Draw slot not reached")]
+    DrawSlotNotReached = 0xfa36,
+
+    #[error("Invalid oracle authority")]
+    InvalidOracleAuthority = 0xfa37,
+
+    #[error("Randomness already fulfilled")]
+    RandomnessFulfilled = 0xfa38,
+
+    #[error("Randomness not fulfilled")]
+    RandomnessNotFulfilled = 0xfa39,
+
+    #[error("Target already revealed")]
+    TargetRevealed = 0xfa3a,
+
+    #[error("Target not revealed")]
+    TargetNotRevealed = 0xfa3b,
+
+    #[error("Ticket page already settled")]
+    PageSettled = 0xfa3c,
+
+    #[error("Tickets not settled")]
+    TicketsNotSettled = 0xfa3d,
+
+    #[error("Round not settled")]
+    RoundNotSettled = 0xfa3e,
+
+    #[error("Reward already claimed")]
+    AlreadyClaimed = 0xfa3f,
+
+    #[error("Reward not claimed")]
+    NotClaimed = 0xfa40,
+
+    #[error("Claim period expired")]
+    ClaimExpired = 0xfa41,
+
+    #[error("Claim period not over")]
+    ClaimPeriodNotOver = 0xfa42,
+
+    #[error("Claims pending")]
+    ClaimsPending = 0xfa43,
+
+    #[error("Unclaimed rewards already swept")]
+    AlreadySwept = 0xfa44,
+
+    #[error("Invalid token")]
+    InvalidToken = 0xfa45,
+
+    #[error("Invalid rollover round")]
+    InvalidRolloverRound = 0xfa46,
+
+    #[error("Round already rolled over")]
+    AlreadyRolledOver = 0xfa47,
+
+    #[error("Rollover pending")]
+    RolloverPending = 0xfa48,
+
+    #[error("Self referral")]
+    SelfReferral = 0xfa49,
+
+    #[error("Referrer locked")]
+    ReferrerLocked = 0xfa4a,
+
+    #[error("Invalid referrer account")]
+    InvalidReferrerAccount = 0xfa4b,
+
+    #[error("Account already migrated")]
+    AlreadyMigrated = 0xfa4c,
+
+    #[error("Withdrawal exceeds available balance")]
+    WithdrawalExceedsAvailable = 0xfa4d,
+
+    #[error("Revenue pending")]
+    RevenuePending = 0xfa4e,
+
+    #[error("No pot surplus")]
+    NoPotSurplus = 0xfa4f,
+
+    #[error("Pot surplus pending")]
+    SurplusPending = 0xfa50,
+
+    #[error("User accounts still open")]
+    UsersPending = 0xfa51,
+
+    #[error("Invalid round")]
+    InvalidRound = 0xfa52,
+
+    #[error("Reveal window passed")]
+    RevealWindowPassed = 0xfa53,
+
+    #[error("Reveal window open")]
+    RevealWindowOpen = 0xfa54,
+
+    #[error("Round still open")]
+    RoundOpen = 0xfa55,
+
+    #[error("Oracle not set")]
+    OracleNotSet = 0xfa56,
 }
 
 impl From<AppError> for ProgramError {
@@ -56,3 +229,40 @@ impl From<AppError> for ProgramError {
         ProgramError::Custom(err as u32)
     }
 }
+
+impl<T> DecodeError<T> for AppError {
+    fn type_of() -> &'static str {
+        "AppError"
+    }
+}
+
+impl PrintProgramError for AppError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
+
+impl AppError {
+    /// Maps a custom error code back to the variant, None for codes this program does not use.
+    pub fn from_code(code: u32) -> Option<AppError> {
+        AppError::from_u32(code)
+    }
+
+    /// Client side decoding of the error an instruction of this program failed with.
+    pub fn from_instruction_error(err: &InstructionError) -> Option<AppError> {
+        match err {
+            InstructionError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    pub fn from_program_error(err: &ProgramError) -> Option<AppError> {
+        match err {
+            ProgramError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+}
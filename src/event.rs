@@ -0,0 +1,119 @@
+#[cfg(feature = "client")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::{RandomnessSource, TicketEntry};
+
+/// prefix of the log lines sol_log_data writes
+pub const PROGRAM_DATA: &str = "Program data: ";
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct TicketsBought {
+    pub round: u64,
+    pub user: Pubkey,
+    pub num: u64,
+    /// total paid, in lamports or in payment_mint base units
+    pub amount: u64,
+    pub payment_mint: Option<Pubkey>,
+    pub referrer: Option<Pubkey>,
+    /// picked or quick-pick shots, in the order they were stored
+    pub tickets: Vec<TicketEntry>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RoundClosed {
+    pub round: u64,
+    pub closer: Pubkey,
+    pub total_shots: u64,
+    pub total_reward: u64,
+    pub randomness_source: RandomnessSource,
+    pub draw_slot: u64,
+    pub randomness_account: Pubkey,
+    pub crank_reward: u64,
+    pub next_round: u64,
+    pub next_total_reward: u64,
+    /// carried into next_total_reward from an earlier round
+    pub rollover: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RewardClaimed {
+    pub round: u64,
+    pub user: Pubkey,
+    pub target: u64,
+    /// tickets hitting each match level, match1 to match6
+    pub matches: [u64; 6],
+    pub reward: u64,
+    /// paid in lamports from the prize pot rather than from the mint vault
+    pub from_pot: bool,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct VaultCleared {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum AppEvent {
+    TicketsBought(TicketsBought),
+    RoundClosed(RoundClosed),
+    RewardClaimed(RewardClaimed),
+    VaultCleared(VaultCleared),
+}
+
+impl AppEvent {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+
+    /// The log line emit produces, as it shows up in transaction logs.
+    #[cfg(feature = "client")]
+    pub fn to_log(&self) -> String {
+        format!("{}{}", PROGRAM_DATA, STANDARD.encode(self.try_to_vec().unwrap()))
+    }
+
+    pub fn decode(data: &[u8]) -> Option<AppEvent> {
+        AppEvent::try_from_slice(data).ok()
+    }
+
+    /// Events in transaction logs, skipping data logged by any other program in the same transaction.
+    #[cfg(feature = "client")]
+    pub fn from_logs<S: AsRef<str>>(program_id: &Pubkey, logs: &[S]) -> Vec<AppEvent> {
+        let program_id = program_id.to_string();
+        let mut invocations: Vec<&str> = vec![];
+        let mut events = vec![];
+        for log in logs {
+            let log = log.as_ref();
+            if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+                if invocations.last() == Some(&program_id.as_str()) {
+                    events.extend(
+                        STANDARD
+                            .decode(data)
+                            .ok()
+                            .and_then(|bytes| AppEvent::decode(&bytes)),
+                    );
+                }
+            } else if let Some(rest) = log.strip_prefix("Program ") {
+                let mut words = rest.split(' ');
+                match (words.next(), words.next()) {
+                    (Some(id), Some("invoke")) => invocations.push(id),
+                    (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                        invocations.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        events
+    }
+}
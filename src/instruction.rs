@@ -1,50 +1,77 @@
+#![allow(clippy::too_many_arguments)]
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     pubkey::Pubkey,
     system_program,
-    sysvar::rent,
+    sysvar::{rent, slot_hashes},
 };
 
-use crate::state::*;
+use crate::{pda::*, state::*};
 
 #[repr(C)]
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum AppInstruction {
-    Configure(ConfigureArgs),
+    InitializeRound(ConfigureArgs),
     BuyTickets(BuyTicketsArgs),
-    CloseRound(),
+    CloseRound(CloseRoundArgs),
     Claim(ClaimArgs),
-    Clear(ClearArgs)
+    Clear(ClearArgs),
+    RevealTarget(RevealTargetArgs),
+    Settle(),
+    Draw(),
+    ConsumeRandomness(),
+    SweepUnclaimed(SweepUnclaimedArgs),
+    UpdateConfig(UpdateConfigArgs),
+    ProposeAuthority(ProposeAuthorityArgs),
+    AcceptAuthority(),
+    CancelAuthority(),
+    InitializeProgramState(GuardianArgs),
+    SetPause(SetPauseArgs),
+    SetGuardian(GuardianArgs),
+    CloseUserAccount(),
+    CloseRoundAccount(),
+    MigrateAccount(MigrateAccountArgs),
+    WithdrawRevenue(WithdrawRevenueArgs),
+    WithdrawPotSurplus(),
 }
 
-pub fn configure(
+pub fn initialize_round(
     program_id: &Pubkey,
     siger: &Pubkey,
     config_info: &Pubkey,
-    round_info: &Pubkey,
+    latest_config_info: &Pubkey,
     mint_info: &Pubkey,
-    mint_vault: &Pubkey,
-    transfer_auth: &Pubkey,
+    payment_mint: Option<&Pubkey>,
     args: ConfigureArgs,
 ) -> Result<Instruction, ProgramError> {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*siger, true),
         AccountMeta::new(*config_info, false),
-        AccountMeta::new(*round_info, false),
+        AccountMeta::new(find_round_address(program_id).0, false),
         AccountMeta::new(*mint_info, false),
-        AccountMeta::new(*mint_vault, false),
-        AccountMeta::new(*transfer_auth, false),
+        AccountMeta::new(find_mint_vault_address(program_id, mint_info).0, false),
+        AccountMeta::new(find_transfer_auth_address(program_id, mint_info).0, false),
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(rent::id(), false),
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
+        AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+        AccountMeta::new_readonly(*latest_config_info, false),
     ];
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new_readonly(*payment_mint, false));
+        accounts.push(AccountMeta::new(find_revenue_vault_address(program_id, payment_mint).0, false));
+        accounts.push(AccountMeta::new_readonly(find_transfer_auth_address(program_id, payment_mint).0, false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
         accounts,
-        data: AppInstruction::Configure(args).try_to_vec().unwrap(),
+        data: AppInstruction::InitializeRound(args).try_to_vec().unwrap(),
     })
 }
 
@@ -54,16 +81,40 @@ pub fn buy(
     config_info: &Pubkey,
     user_info: &Pubkey, 
     charge_info: &Pubkey, 
+    page_info: &Pubkey,
+    next_page_info: &Pubkey,
+    revenue_recipients: &[Pubkey],
+    pot_mode: bool,
+    referrer_accounts: Option<(&Pubkey, &Pubkey)>,
+    payment_accounts: Option<(&Pubkey, &Pubkey)>,
     args: BuyTicketsArgs
 ) -> Result<Instruction, ProgramError> {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*siger, true),
         AccountMeta::new(*config_info, false),
         AccountMeta::new(*user_info, false), 
         AccountMeta::new(*charge_info, false), 
         AccountMeta::new_readonly(rent::id(), false),  
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*page_info, false),
+        AccountMeta::new(*next_page_info, false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
     ];
+    for recipient in revenue_recipients {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+    if pot_mode {
+        accounts.push(AccountMeta::new(find_prize_pot_address(program_id).0, false));
+    }
+    if let Some((referrer_account, referrer_stats)) = referrer_accounts {
+        accounts.push(AccountMeta::new(*referrer_account, false));
+        accounts.push(AccountMeta::new(*referrer_stats, false));
+    }
+    if let Some((token_account, revenue_vault)) = payment_accounts {
+        accounts.push(AccountMeta::new(*token_account, false));
+        accounts.push(AccountMeta::new(*revenue_vault, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
     
     Ok(Instruction {
         program_id: *program_id,
@@ -76,22 +127,32 @@ pub fn close(
     program_id: &Pubkey,
     siger: &Pubkey,
     config_info: &Pubkey,
-    round_info: &Pubkey,
     new_config_info: &Pubkey,
+    mint_info: &Pubkey,
+    randomness_info: Option<&Pubkey>,
+    rollover_config: Option<&Pubkey>,
+    args: CloseRoundArgs,
 ) -> Result<Instruction, ProgramError> {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*siger, true),
         AccountMeta::new(*config_info, false),
-        AccountMeta::new(*round_info, false),
+        AccountMeta::new(find_round_address(program_id).0, false),
         AccountMeta::new(*new_config_info, false),
         AccountMeta::new_readonly(rent::id(), false),
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
     ];
+    if let Some(randomness_info) = randomness_info {
+        accounts.push(AccountMeta::new_readonly(*randomness_info, false));
+    }
+    if let Some(rollover_config) = rollover_config {
+        accounts.push(AccountMeta::new(*rollover_config, false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
         accounts,
-        data: AppInstruction::CloseRound().try_to_vec().unwrap(),
+        data: AppInstruction::CloseRound(args).try_to_vec().unwrap(),
     })
 }
 
@@ -101,23 +162,27 @@ pub fn claim(
     config_info: &Pubkey,
     mint_info: &Pubkey,
     user_info: &Pubkey, 
-    mint_vault: &Pubkey,
-    transfer_auth: &Pubkey,
     token_account: &Pubkey, 
+    pot_mode: bool,
     args: ClaimArgs
 ) -> Result<Instruction, ProgramError> {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(*siger, true),
         AccountMeta::new(*config_info, false),
         AccountMeta::new(*mint_info, false),
         AccountMeta::new(*user_info, false), 
-        AccountMeta::new(*mint_vault, false), 
-        AccountMeta::new(*transfer_auth, false), 
+        AccountMeta::new(find_mint_vault_address(program_id, mint_info).0, false), 
+        AccountMeta::new(find_transfer_auth_address(program_id, mint_info).0, false), 
         AccountMeta::new(*token_account, false), 
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(rent::id(), false),  
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
     ];
+    if pot_mode {
+        accounts.push(AccountMeta::new(find_prize_pot_address(program_id).0, false));
+    }
     
     Ok(Instruction {
         program_id: *program_id,
@@ -131,8 +196,6 @@ pub fn clear(
     siger: &Pubkey,
     config_info: &Pubkey,
     mint_info: &Pubkey,
-    mint_vault: &Pubkey,
-    transfer_auth: &Pubkey,
     token_account: &Pubkey, 
     args: ClearArgs
 ) -> Result<Instruction, ProgramError> {
@@ -140,12 +203,14 @@ pub fn clear(
         AccountMeta::new(*siger, true),
         AccountMeta::new(*config_info, false),
         AccountMeta::new(*mint_info, false), 
-        AccountMeta::new(*mint_vault, false), 
-        AccountMeta::new(*transfer_auth, false), 
+        AccountMeta::new(find_mint_vault_address(program_id, mint_info).0, false), 
+        AccountMeta::new(find_transfer_auth_address(program_id, mint_info).0, false), 
         AccountMeta::new(*token_account, false), 
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(rent::id(), false),  
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_mint_ledger_address(program_id, mint_info).0, false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
     ];
     
     Ok(Instruction {
@@ -153,4 +218,356 @@ pub fn clear(
         accounts,
         data: AppInstruction::Clear(args).try_to_vec().unwrap(),
     })
-}
\ No newline at end of file
+}
+
+pub fn reveal_target(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    args: RevealTargetArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(slot_hashes::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::RevealTarget(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn settle(
+    program_id: &Pubkey,
+    config_info: &Pubkey,
+    mint_info: &Pubkey,
+    user: &Pubkey,
+    user_info: &Pubkey,
+    page_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new(*user_info, false),
+        AccountMeta::new(*page_info, false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::Settle().try_to_vec().unwrap(),
+    })
+}
+
+pub fn draw(
+    program_id: &Pubkey,
+    config_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(slot_hashes::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::Draw().try_to_vec().unwrap(),
+    })
+}
+
+pub fn consume_randomness(
+    program_id: &Pubkey,
+    config_info: &Pubkey,
+    randomness_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(*randomness_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::ConsumeRandomness().try_to_vec().unwrap(),
+    })
+}
+
+pub fn sweep_unclaimed(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    destination: &Pubkey,
+    mint_info: &Pubkey,
+    pot_mode: bool,
+    args: SweepUnclaimedArgs,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(*mint_info, false),
+        AccountMeta::new(find_mint_vault_address(program_id, mint_info).0, false),
+        AccountMeta::new_readonly(find_transfer_auth_address(program_id, mint_info).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
+        AccountMeta::new_readonly(find_round_address(program_id).0, false),
+    ];
+    if pot_mode {
+        accounts.push(AccountMeta::new(find_prize_pot_address(program_id).0, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::SweepUnclaimed(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn update_config(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    mint_info: &Pubkey,
+    args: UpdateConfigArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new(find_mint_ledger_address(program_id, mint_info).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::UpdateConfig(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn propose_authority(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    args: ProposeAuthorityArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::ProposeAuthority(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn accept_authority(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::AcceptAuthority().try_to_vec().unwrap(),
+    })
+}
+
+pub fn cancel_authority(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::CancelAuthority().try_to_vec().unwrap(),
+    })
+}
+
+pub fn initialize_program_state(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    args: GuardianArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(find_program_state_address(program_id).0, false),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::InitializeProgramState(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn set_pause(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    args: SetPauseArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*guardian, true),
+        AccountMeta::new(find_program_state_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::SetPause(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn set_guardian(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    args: GuardianArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*guardian, true),
+        AccountMeta::new(find_program_state_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::SetGuardian(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn close_user_account(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    user: &Pubkey,
+    user_info: &Pubkey,
+    page_infos: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new(*user, false),
+        AccountMeta::new(*user_info, false),
+    ];
+    for page_info in page_infos {
+        accounts.push(AccountMeta::new(*page_info, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::CloseUserAccount().try_to_vec().unwrap(),
+    })
+}
+
+pub fn close_round_account(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*config_info, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::CloseRoundAccount().try_to_vec().unwrap(),
+    })
+}
+
+pub fn migrate_account(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    account: &Pubkey,
+    config_info: &Pubkey,
+    user: Option<&Pubkey>,
+    mint: Option<&Pubkey>,
+    args: MigrateAccountArgs,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*siger, true),
+        AccountMeta::new(*account, false),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(user) = user {
+        accounts.push(AccountMeta::new_readonly(*user, false));
+    }
+    if let Some(mint) = mint {
+        accounts.push(AccountMeta::new(find_mint_ledger_address(program_id, mint).0, false));
+        accounts.push(AccountMeta::new_readonly(rent::id(), false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::MigrateAccount(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn withdraw_revenue(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    payment_mint: &Pubkey,
+    token_account: &Pubkey,
+    args: WithdrawRevenueArgs,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new_readonly(*payment_mint, false),
+        AccountMeta::new(find_revenue_vault_address(program_id, payment_mint).0, false),
+        AccountMeta::new_readonly(find_transfer_auth_address(program_id, payment_mint).0, false),
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::WithdrawRevenue(args).try_to_vec().unwrap(),
+    })
+}
+
+pub fn withdraw_pot_surplus(
+    program_id: &Pubkey,
+    siger: &Pubkey,
+    config_info: &Pubkey,
+    destination: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*siger, true),
+        AccountMeta::new(*config_info, false),
+        AccountMeta::new(find_prize_pot_address(program_id).0, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(find_program_state_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: AppInstruction::WithdrawPotSurplus().try_to_vec().unwrap(),
+    })
+}
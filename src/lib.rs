@@ -1,6 +1,10 @@
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
+pub mod builder;
 pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod pda;
 pub mod processor;
 pub mod state;
 pub mod utils;
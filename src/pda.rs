@@ -0,0 +1,100 @@
+//! Program derived addresses, shared by the processors and by clients.
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const ROUND_SEED: &[u8] = b"round";
+pub const USER_INFO_SEED: &[u8] = b"user_info";
+pub const TICKET_PAGE_SEED: &[u8] = b"ticket_page";
+pub const MINT_VAULT_SEED: &[u8] = b"mint_vault";
+pub const MINT_LEDGER_SEED: &[u8] = b"mint_ledger";
+pub const TRANSFER_AUTH_SEED: &[u8] = b"transfer_auth";
+pub const REVENUE_VAULT_SEED: &[u8] = b"revenue_vault";
+pub const PROGRAM_STATE_SEED: &[u8] = b"program_state";
+pub const PRIZE_POT_SEED: &[u8] = b"prize_pot";
+pub const REFERRER_STATS_SEED: &[u8] = b"referrer_stats";
+
+/// Round config, one per round number.
+pub fn find_config_address(program_id: &Pubkey, round: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[program_id.as_ref(), CONFIG_SEED, round.to_string().as_bytes()],
+        program_id,
+    )
+}
+
+/// Round counter, one per program.
+pub fn find_round_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), ROUND_SEED], program_id)
+}
+
+/// A user's entry in a round.
+pub fn find_user_info_address(program_id: &Pubkey, user: &Pubkey, round: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            program_id.as_ref(),
+            user.as_ref(),
+            USER_INFO_SEED,
+            round.to_string().as_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// One page of a user's tickets in a round.
+pub fn find_ticket_page_address(program_id: &Pubkey, user: &Pubkey, round: u64, page: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            program_id.as_ref(),
+            user.as_ref(),
+            TICKET_PAGE_SEED,
+            round.to_string().as_bytes(),
+            page.to_string().as_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Token account holding the rewards paid in `mint`.
+pub fn find_mint_vault_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), mint.as_ref(), MINT_VAULT_SEED], program_id)
+}
+
+/// Rewards the rounds paying out `mint` still owe from its vault.
+pub fn find_mint_ledger_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), mint.as_ref(), MINT_LEDGER_SEED], program_id)
+}
+
+/// Owner of the vaults of `mint`.
+pub fn find_transfer_auth_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), mint.as_ref(), TRANSFER_AUTH_SEED], program_id)
+}
+
+/// Token account collecting ticket revenue paid in `payment_mint`.
+pub fn find_revenue_vault_address(program_id: &Pubkey, payment_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[program_id.as_ref(), payment_mint.as_ref(), REVENUE_VAULT_SEED],
+        program_id,
+    )
+}
+
+/// Guardian and pause flags.
+pub fn find_program_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), PROGRAM_STATE_SEED], program_id)
+}
+
+/// ProgramData account of the upgradeable loader, holding the upgrade authority.
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+}
+
+/// Lamports held for prizes of rounds in pot mode.
+pub fn find_prize_pot_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref(), PRIZE_POT_SEED], program_id)
+}
+
+/// Referral totals of `referrer` across rounds.
+pub fn find_referrer_stats_address(program_id: &Pubkey, referrer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[program_id.as_ref(), referrer.as_ref(), REFERRER_STATS_SEED],
+        program_id,
+    )
+}
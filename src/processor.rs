@@ -3,8 +3,8 @@ use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg,
 
 use crate::instruction::*;
 
-pub mod configure;
-pub use configure::*;
+pub mod initialize_round;
+pub use initialize_round::*;
 
 pub mod buy_tickets;
 pub use buy_tickets::*;
@@ -18,6 +18,57 @@ pub use claim::*;
 pub mod clear;
 pub use clear::*;
 
+pub mod reveal_target;
+pub use reveal_target::*;
+
+pub mod settle;
+pub use settle::*;
+
+pub mod draw;
+pub use draw::*;
+
+pub mod consume_randomness;
+pub use consume_randomness::*;
+
+pub mod sweep_unclaimed;
+pub use sweep_unclaimed::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod propose_authority;
+pub use propose_authority::*;
+
+pub mod accept_authority;
+pub use accept_authority::*;
+
+pub mod cancel_authority;
+pub use cancel_authority::*;
+
+pub mod initialize_program_state;
+pub use initialize_program_state::*;
+
+pub mod set_pause;
+pub use set_pause::*;
+
+pub mod set_guardian;
+pub use set_guardian::*;
+
+pub mod close_user_account;
+pub use close_user_account::*;
+
+pub mod close_round_account;
+pub use close_round_account::*;
+
+pub mod migrate_account;
+pub use migrate_account::*;
+
+pub mod withdraw_revenue;
+pub use withdraw_revenue::*;
+
+pub mod withdraw_pot_surplus;
+pub use withdraw_pot_surplus::*;
+
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -26,17 +77,17 @@ pub fn process_instruction(
 ) -> ProgramResult {
     let instruction = AppInstruction::try_from_slice(input)?;
     match instruction {
-        AppInstruction::Configure(args) => {
-            msg!("Instruction: Configure");
-            process_configure(program_id, accounts, args)
+        AppInstruction::InitializeRound(args) => {
+            msg!("Instruction: Initialize Round");
+            process_initialize_round(program_id, accounts, args)
         }
         AppInstruction::BuyTickets(args) => {
             msg!("Instruction: Buy Tickets");
             process_buy(program_id, accounts, args)
         }
-        AppInstruction::CloseRound() => {
+        AppInstruction::CloseRound(args) => {
             msg!("Instruction: Start new round");
-            process_close(program_id, accounts)
+            process_close(program_id, accounts, args)
         }
         AppInstruction::Claim(args) => {
             msg!("Instruction: Claim");
@@ -46,5 +97,73 @@ pub fn process_instruction(
             msg!("Instruction: Clear");
             process_clear(program_id, accounts, args)
         }
+        AppInstruction::RevealTarget(args) => {
+            msg!("Instruction: Reveal Target");
+            process_reveal_target(program_id, accounts, args)
+        }
+        AppInstruction::Settle() => {
+            msg!("Instruction: Settle");
+            process_settle(program_id, accounts)
+        }
+        AppInstruction::Draw() => {
+            msg!("Instruction: Draw");
+            process_draw(program_id, accounts)
+        }
+        AppInstruction::ConsumeRandomness() => {
+            msg!("Instruction: Consume Randomness");
+            process_consume_randomness(program_id, accounts)
+        }
+        AppInstruction::SweepUnclaimed(args) => {
+            msg!("Instruction: Sweep Unclaimed");
+            process_sweep_unclaimed(program_id, accounts, args)
+        }
+        AppInstruction::UpdateConfig(args) => {
+            msg!("Instruction: Update Config");
+            process_update_config(program_id, accounts, args)
+        }
+        AppInstruction::ProposeAuthority(args) => {
+            msg!("Instruction: Propose Authority");
+            process_propose_authority(program_id, accounts, args)
+        }
+        AppInstruction::AcceptAuthority() => {
+            msg!("Instruction: Accept Authority");
+            process_accept_authority(program_id, accounts)
+        }
+        AppInstruction::CancelAuthority() => {
+            msg!("Instruction: Cancel Authority");
+            process_cancel_authority(program_id, accounts)
+        }
+        AppInstruction::InitializeProgramState(args) => {
+            msg!("Instruction: Initialize Program State");
+            process_initialize_program_state(program_id, accounts, args)
+        }
+        AppInstruction::SetPause(args) => {
+            msg!("Instruction: Set Pause");
+            process_set_pause(program_id, accounts, args)
+        }
+        AppInstruction::SetGuardian(args) => {
+            msg!("Instruction: Set Guardian");
+            process_set_guardian(program_id, accounts, args)
+        }
+        AppInstruction::CloseUserAccount() => {
+            msg!("Instruction: Close User Account");
+            process_close_user_account(program_id, accounts)
+        }
+        AppInstruction::CloseRoundAccount() => {
+            msg!("Instruction: Close Round Account");
+            process_close_round_account(program_id, accounts)
+        }
+        AppInstruction::MigrateAccount(args) => {
+            msg!("Instruction: Migrate Account");
+            process_migrate_account(program_id, accounts, args)
+        }
+        AppInstruction::WithdrawRevenue(args) => {
+            msg!("Instruction: Withdraw Revenue");
+            process_withdraw_revenue(program_id, accounts, args)
+        }
+        AppInstruction::WithdrawPotSurplus() => {
+            msg!("Instruction: Withdraw Pot Surplus");
+            process_withdraw_pot_surplus(program_id, accounts)
+        }
         }
     }
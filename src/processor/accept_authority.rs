@@ -0,0 +1,28 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_accept_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    if config_data.pending_authority != Some(*signer_info.key) {
+        return Err(AppError::InvalidPendingAuthority.into());
+    }
+
+    config_data.authority = *signer_info.key;
+    config_data.pending_authority = None;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
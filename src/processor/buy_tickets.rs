@@ -1,12 +1,11 @@
-use crate::{ferror, state::*, utils::*};
-use arrayref::array_ref;
+use crate::{error::AppError, event::*, pda::*, state::*, utils::*};
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     keccak::hashv,
-    msg,
     program::invoke,
+    program_pack::Pack,
     program_error::ProgramError,
     pubkey::Pubkey,
     system_instruction, sysvar,
@@ -24,28 +23,56 @@ pub fn process_buy(
     let charge_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
     let system_info = next_account_info(account_info_iter)?;
+    let page_info = next_account_info(account_info_iter)?;
+    let next_page_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
 
-    assert_signer(&signer_info)?;
-    assert_eq_pubkey_0(&rent_info, &sysvar::rent::id())?;
-    assert_eq_pubkey_1(&system_info, &solana_program::system_program::id())?;
+    assert_signer(signer_info)?;
+    assert_program_state(program_id, program_state_info)?;
+    if ProgramState::load_or_default(program_state_info)?.paused_buys {
+        return Err(AppError::BuysPaused.into());
+    }
+    assert_eq_pubkey_0(rent_info, &sysvar::rent::id())?;
+    assert_eq_pubkey_1(system_info, &solana_program::system_program::id())?;
 
     let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_eq_pubkey(charge_info, &config_data.charge_addr)?;
     let now_ts = now_timestamp();
     //check sale state
     if config_data.start_time > now_ts || config_data.closed {
-        return ferror!("sale not open");
+        return Err(AppError::SaleNotOpen.into());
+    }
+    if config_data.end_time != 0 && now_ts >= config_data.end_time {
+        return Err(AppError::SaleEnded.into());
+    }
+    if config_data.revealed {
+        return Err(AppError::TargetRevealed.into());
+    }
+    if config_data.randomness_source == RandomnessSource::CommitReveal && config_data.commitment == [0; 32] {
+        return Err(AppError::CommitmentNotSet.into());
+    }
+    if args.num == 0 || args.num > config_data.max_batch {
+        return Err(AppError::InvalidTicketNum.into());
     }
-    let round = config_data.round.to_string();
-    let user_bump = assert_user_info(program_id, &signer_info.key, user_info, round.clone())?;
+    if let Some(shot) = args.shot {
+        if shot.iter().any(|&digit| digit > 9) {
+            return Err(AppError::InvalidShot.into());
+        }
+    }
+    let round = config_data.round;
+    let user_bump = assert_user_info(program_id, signer_info.key, user_info, round)?;
+    let round_seed = round.to_string();
     let user_seeds = [
         program_id.as_ref(),
         signer_info.key.as_ref(),
-        "user_info".as_bytes(),
-        round.as_bytes(),
+        USER_INFO_SEED,
+        round_seed.as_bytes(),
         &[user_bump],
     ];
 
-    if user_info.data_is_empty() {
+    let user_created = user_info.data_is_empty();
+    if user_created {
+        config_data.open_user_accounts += 1;
         create_or_allocate_account_raw(
             *program_id,
             user_info,
@@ -57,82 +84,234 @@ pub fn process_buy(
         )?;
     }
 
-    let price = 50000000 as u64;
-    let mut user_data = UserData::from_account_info(user_info)?;
-    invoke(
-        &system_instruction::transfer(&signer_info.key, &charge_info.key.clone(), price * args.num),
-        &[
-            signer_info.clone(),
-            charge_info.clone(),
-            system_info.clone(),
-        ],
-    )?;
-    let shot: [u8; 6];
-
-    let hash = hashv(&[
-        &now_timestamp().to_be_bytes().as_slice(),
-        &signer_info.key.as_ref(),
-    ]);
-    let kep_bytes = hash.to_bytes();
-    let bts = array_ref![kep_bytes, 0, 16];
-    let num = u128::from_be_bytes(*bts);
-    let fnum = (num % 1000000) as u64;
-    let array_u8: [u8; 6] = [
-        ((fnum / 100000) % 10) as u8,
-        ((fnum / 10000) % 10) as u8,
-        ((fnum / 1000) % 10) as u8,
-        ((fnum / 100) % 10) as u8,
-        ((fnum / 10) % 10) as u8,
-        (fnum % 10) as u8,
-    ];
-    shot = array_u8;
-    user_data.shots.insert(array_u8, args.num);
-
-    let target_array: [u8; 6] = [
-        ((config_data.target / 100000) % 10) as u8,
-        ((config_data.target / 10000) % 10) as u8,
-        ((config_data.target / 1000) % 10) as u8,
-        ((config_data.target / 100) % 10) as u8,
-        ((config_data.target / 10) % 10) as u8,
-        (config_data.target % 10) as u8,
-    ];
-
-    let matched = count_matching_elements_until_difference(&shot, &target_array) as u8;
-
-    match matched {
-        1 => {
-            user_data.match1 += args.num;
-            config_data.match1 += args.num;
+    let mut user_data = if user_created {
+        UserData::new()
+    } else {
+        UserData::from_account_info(user_info)?
+    };
+    let amount = config_data
+        .ticket_price
+        .checked_mul(args.num)
+        .ok_or(AppError::CheckedCalculateFailed)?;
+    // recipient accounts follow program_state, in the order of the configured shares
+    let mut recipients = vec![];
+    for share in config_data.active_revenue_shares() {
+        let recipient_info = next_account_info(account_info_iter)?;
+        assert_eq_pubkey(recipient_info, &share.recipient)?;
+        recipients.push((recipient_info, share.amount(amount)));
+    }
+    if config_data.is_pot_mode() {
+        let prize_pot = next_account_info(account_info_iter)?;
+        let pot_bump = assert_prize_pot(program_id, prize_pot)?;
+        if prize_pot.owner != program_id {
+            create_or_allocate_account_raw(
+                *program_id,
+                prize_pot,
+                rent_info,
+                system_info,
+                signer_info,
+                0,
+                &[program_id.as_ref(), PRIZE_POT_SEED, &[pot_bump]],
+            )?;
+        }
+        let pot_share = config_data.pot_share(amount);
+        config_data.pot_collected += pot_share;
+        recipients.push((prize_pot, pot_share));
+    }
+    // the referrer is fixed by the user's first purchase of the round
+    let first_purchase = user_data.total_shots == 0;
+    if first_purchase {
+        if args.referrer == Some(*signer_info.key) {
+            return Err(AppError::SelfReferral.into());
+        }
+        user_data.referrer = args.referrer;
+    } else if args.referrer != user_data.referrer {
+        return Err(AppError::ReferrerLocked.into());
+    }
+    if let Some(referrer) = user_data.referrer {
+        let referrer_account = next_account_info(account_info_iter)?;
+        let referrer_stats_info = next_account_info(account_info_iter)?;
+        let stats_bump = assert_referrer_stats(program_id, &referrer, referrer_stats_info)?;
+        let stats_created = referrer_stats_info.data_is_empty();
+        if stats_created {
+            create_or_allocate_account_raw(
+                *program_id,
+                referrer_stats_info,
+                rent_info,
+                system_info,
+                signer_info,
+                ReferrerStats::LEN,
+                &[
+                    program_id.as_ref(),
+                    referrer.as_ref(),
+                    REFERRER_STATS_SEED,
+                    &[stats_bump],
+                ],
+            )?;
+        }
+        let mut stats = if stats_created {
+            ReferrerStats::new()
+        } else {
+            ReferrerStats::from_account_info(referrer_stats_info)?
+        };
+        let referral_share = config_data.referral_share(amount);
+        if config_data.payment_mint.is_some() {
+            let token_account = spl_token::state::Account::unpack(&referrer_account.data.borrow())?;
+            if token_account.owner != referrer {
+                return Err(AppError::InvalidReferrerAccount.into());
+            }
+            stats.tokens_earned += referral_share;
+        } else {
+            assert_eq_pubkey(referrer_account, &referrer)?;
+            stats.lamports_earned += referral_share;
         }
-        2 => {
-            user_data.match2 += args.num;
-            config_data.match2 += args.num;
+        if first_purchase {
+            stats.referrals += 1;
         }
-        3 => {
-            user_data.match3 += args.num;
-            config_data.match3 += args.num;
+        stats.referrer = referrer;
+        stats.tickets += args.num;
+        stats.serialize(&mut &mut referrer_stats_info.data.borrow_mut()[..])?;
+        recipients.push((referrer_account, referral_share));
+    }
+    let rest = amount - recipients.iter().map(|(_, share)| share).sum::<u64>();
+    match config_data.payment_mint {
+        Some(payment_mint) => {
+            let token_account = next_account_info(account_info_iter)?;
+            let revenue_vault = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            assert_eq_pubkey_2(token_program_info, &spl_token::id())?;
+            assert_revenue_vault(program_id, &payment_mint, revenue_vault)?;
+            for (recipient_info, share) in recipients {
+                spl_token_transfer_invoke(
+                    token_program_info.clone(),
+                    token_account.clone(),
+                    recipient_info.clone(),
+                    signer_info.clone(),
+                    share,
+                )?;
+            }
+            spl_token_transfer_invoke(
+                token_program_info.clone(),
+                token_account.clone(),
+                revenue_vault.clone(),
+                signer_info.clone(),
+                rest,
+            )?;
+            config_data.revenue += rest;
         }
-        4 => {
-            user_data.match4 += args.num;
-            config_data.match4 += args.num;
+        None => {
+            recipients.push((charge_info, rest));
+            for (recipient_info, share) in recipients {
+                invoke(
+                    &system_instruction::transfer(signer_info.key, recipient_info.key, share),
+                    &[
+                        signer_info.clone(),
+                        recipient_info.clone(),
+                        system_info.clone(),
+                    ],
+                )?;
+            }
         }
-        5 => {
-            user_data.match5 += args.num;
-            config_data.match5 += args.num;
+    }
+
+    // the target is still hidden here, tiers are counted by Settle after the reveal
+    let mut entries = vec![];
+    match args.shot {
+        Some(shot) => {
+            entries.push(TicketEntry { shot, num: args.num });
         }
-        6 => {
-            user_data.match6 += args.num;
-            config_data.match6 += args.num;
+        None => {
+            // every quick-pick ticket draws its own number from the player's ticket counter,
+            // drawing again when it repeats a number of the same batch
+            let now_ts = now_ts.to_be_bytes();
+            let mut nonce = user_data.total_shots;
+            for _ in 0..args.num {
+                let shot = loop {
+                    let hash = hashv(&[
+                        now_ts.as_slice(),
+                        signer_info.key.as_ref(),
+                        nonce.to_be_bytes().as_slice(),
+                    ]);
+                    nonce += 1;
+                    let shot = number_to_digits(hash_to_number(&hash));
+                    if !entries.iter().any(|entry: &TicketEntry| entry.shot == shot) {
+                        break shot;
+                    }
+                };
+                entries.push(TicketEntry { shot, num: 1 });
+            }
         }
+    }
 
-        _ => {}
+    let tickets = entries.clone();
+    // append to the last page, spilling over into a freshly allocated one when it fills up
+    let page_index = user_data.pages.saturating_sub(1);
+    // the config outlives every user account, so CloseUserAccount can always check them against it
+    config_data.open_user_accounts += page_info.data_is_empty() as u64;
+    let mut page = load_ticket_page(program_id, signer_info, page_info, rent_info, system_info, round, page_index)?;
+    let fits = entries.len().min(TICKETS_PER_PAGE - page.tickets.len());
+    page.tickets.extend(entries.drain(..fits));
+    page.serialize(&mut &mut page_info.data.borrow_mut()[..])?;
+    user_data.pages = page_index + 1;
+    if !entries.is_empty() {
+        config_data.open_user_accounts += next_page_info.data_is_empty() as u64;
+        let mut next_page = load_ticket_page(program_id, signer_info, next_page_info, rent_info, system_info, round, page_index + 1)?;
+        next_page.tickets.extend(entries);
+        next_page.serialize(&mut &mut next_page_info.data.borrow_mut()[..])?;
+        user_data.pages = page_index + 2;
     }
 
     user_data.round = config_data.round;
     user_data.total_shots += args.num;
-    user_data.serialize(&mut *user_info.try_borrow_mut_data()?)?;
+    user_data.serialize(&mut &mut user_info.data.borrow_mut()[..])?;
 
     config_data.total_shots += args.num;
-    config_data.serialize(&mut *config_info.try_borrow_mut_data()?)?;
-    Ok(())
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    AppEvent::TicketsBought(TicketsBought {
+        round: config_data.round,
+        user: *signer_info.key,
+        num: args.num,
+        amount,
+        payment_mint: config_data.payment_mint,
+        referrer: user_data.referrer,
+        tickets,
+    })
+    .emit()
+}
+
+fn load_ticket_page<'a>(
+    program_id: &Pubkey,
+    signer_info: &AccountInfo<'a>,
+    page_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+    system_info: &AccountInfo<'a>,
+    round: u64,
+    page: u64,
+) -> Result<TicketPage, ProgramError> {
+    let page_bump = assert_ticket_page(program_id, signer_info.key, page_info, round, page)?;
+    if page_info.data_is_empty() {
+        create_or_allocate_account_raw(
+            *program_id,
+            page_info,
+            rent_info,
+            system_info,
+            signer_info,
+            TicketPage::LEN,
+            &[
+                program_id.as_ref(),
+                signer_info.key.as_ref(),
+                TICKET_PAGE_SEED,
+                round.to_string().as_bytes(),
+                page.to_string().as_bytes(),
+                &[page_bump],
+            ],
+        )?;
+        return Ok(TicketPage {
+            round,
+            page,
+            ..TicketPage::new()
+        });
+    }
+    TicketPage::from_account_info(page_info)
 }
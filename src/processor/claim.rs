@@ -1,10 +1,8 @@
-use crate::{ferror, state::*, utils::*};
+use crate::{error::AppError, event::*, pda::*, state::*, utils::*};
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    msg,
-    program_error::ProgramError,
     pubkey::Pubkey,
     sysvar,
 };
@@ -21,64 +19,96 @@ pub fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo], args: ClaimA
     let token_program_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
     let system_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
 
-    assert_signer(&signer_info)?;
-    assert_eq_pubkey(&token_program_info, &spl_token::id())?;
-    assert_eq_pubkey(&rent_info, &sysvar::rent::id())?;
-    assert_eq_pubkey(&system_info, &solana_program::system_program::id())?;
+    assert_signer(signer_info)?;
+    assert_claims_not_paused(program_id, program_state_info)?;
+    assert_eq_pubkey(token_program_info, &spl_token::id())?;
+    assert_eq_pubkey(rent_info, &sysvar::rent::id())?;
+    assert_eq_pubkey(system_info, &solana_program::system_program::id())?;
 
-    let round = args.round.to_string();
-    assert_user_info(program_id, &signer_info.key, user_info, round.clone())?;
-    assert_config(&program_id, &config_info, round.clone())?;
+    assert_user_info(program_id, signer_info.key, user_info, args.round)?;
+    assert_config(program_id, config_info, args.round)?;
     assert_mint_vault(program_id, mint_info, mint_vault)?;
 
-    let config_data = ConfigureData::from_account_info(config_info)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    // every mint's vault answers to the same program, the reward is only paid in the round's own
+    assert_eq_pubkey(mint_info, &config_data.token)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+    let outstanding = config_data.outstanding_reward();
     if !config_data.closed {
-        return ferror!("sale not closed");
+        return Err(AppError::SaleNotClosed.into());
+    }
+    if !config_data.revealed {
+        return Err(AppError::TargetNotRevealed.into());
+    }
+    if config_data.settled_shots != config_data.total_shots {
+        return Err(AppError::RoundNotSettled.into());
+    }
+    if config_data.claim_deadline != 0 && now_timestamp() > config_data.claim_deadline {
+        return Err(AppError::ClaimExpired.into());
     }
     let auth_bump = assert_tranfer_authority(program_id, mint_info, transfer_auth)?;
     let authority_seed = [
         program_id.as_ref(),
         mint_info.key.as_ref(),
-        "transfer_auth".as_bytes(),
+        TRANSFER_AUTH_SEED,
         &[auth_bump],
     ];
 
     let mut user_data = UserData::from_account_info(user_info)?;
     if user_data.claimed {
-        return ferror!("claimed");
-    }
-    let mut reward = 0;
-    if config_data.match1 > 0 {
-        reward += config_data.total_reward * 2 / 100 * user_data.match1 / config_data.match1;
-    }
-    if config_data.match2 > 0 {
-        reward += config_data.total_reward * 3 / 100 * user_data.match2 / config_data.match2;
-    }
-    if config_data.match3 > 0 {
-        reward += config_data.total_reward * 5 / 100 * user_data.match3 / config_data.match3;
+        return Err(AppError::AlreadyClaimed.into());
     }
-    if config_data.match4 > 0 {
-        reward += config_data.total_reward * 20 / 100 * user_data.match4 / config_data.match4;
+    if user_data.settled_pages != user_data.pages {
+        return Err(AppError::TicketsNotSettled.into());
     }
-    if config_data.match5 > 0 {
-        reward += config_data.total_reward * 30 / 100 * user_data.match5 / config_data.match5;
-    }
-    if config_data.match6 > 0 {
-        reward += config_data.total_reward * 40 / 100 * user_data.match6 / config_data.match6;
+    let mut reward = 0;
+    let config_matches = config_data.matches();
+    let user_matches = user_data.matches();
+    for tier in 0..config_matches.len() {
+        reward += (config_data.tier_reward(tier) as u128 * user_matches[tier] as u128)
+            .checked_div(config_matches[tier] as u128)
+            .unwrap_or(0) as u64;
     }
 
-    spl_token_transfer(
-        token_program_info.clone(),
-        mint_vault.clone(),
-        token_account.clone(),
-        transfer_auth.clone(),
-        reward as u64,
-        &authority_seed
-    )?;
+    if config_data.is_pot_mode() {
+        let prize_pot = next_account_info(account_info_iter)?;
+        assert_owned_by(prize_pot, program_id)?;
+        assert_prize_pot(program_id, prize_pot)?;
+        withdraw_lamports(prize_pot, signer_info, reward)?;
+    } else {
+        spl_token_transfer(
+            token_program_info.clone(),
+            mint_vault.clone(),
+            token_account.clone(),
+            transfer_auth.clone(),
+            reward,
+            &authority_seed
+        )?;
+    }
     
     user_data.claimed = true;
     user_data.reward = reward;
-    user_data.serialize(&mut *user_info.try_borrow_mut_data()?)?;
-    Ok(())
+    user_data.serialize(&mut &mut user_info.data.borrow_mut()[..])?;
+
+    config_data.claimed += reward;
+    config_data.claimed_winners += user_data.is_winner() as u64;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    mint_ledger.track(outstanding, &config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+
+    AppEvent::RewardClaimed(RewardClaimed {
+        round: config_data.round,
+        user: *signer_info.key,
+        target: config_data.target,
+        matches: user_matches,
+        reward,
+        from_pot: config_data.is_pot_mode(),
+    })
+    .emit()
 }
@@ -1,7 +1,8 @@
-use crate::{state::*, utils::*};
+use crate::{error::AppError, event::*, pda::*, state::*, utils::*};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    program_pack::Pack,
     pubkey::Pubkey,
     sysvar,
 };
@@ -17,25 +18,39 @@ pub fn process_clear(program_id: &Pubkey, accounts: &[AccountInfo], args: ClearA
     let token_program_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
     let system_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
 
-    assert_signer(&signer_info)?;
-    assert_eq_pubkey(&token_program_info, &spl_token::id())?;
-    assert_eq_pubkey(&rent_info, &sysvar::rent::id())?;
-    assert_eq_pubkey(&system_info, &solana_program::system_program::id())?;
+    assert_signer(signer_info)?;
+    assert_claims_not_paused(program_id, program_state_info)?;
+    assert_eq_pubkey(token_program_info, &spl_token::id())?;
+    assert_eq_pubkey(rent_info, &sysvar::rent::id())?;
+    assert_eq_pubkey(system_info, &solana_program::system_program::id())?;
 
-    assert_owned_by(config_info, &program_id)?;
+    assert_owned_by(config_info, program_id)?;
     let config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
     assert_eq_pubkey(signer_info, &config_data.authority)?;
+    assert_eq_pubkey(mint_info, &config_data.token)?;
+    assert_mint_vault(program_id, mint_info, mint_vault)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, mint_info.key, mint_ledger_info)?;
+
+    // the vault is shared by every round of the mint, only tokens none of them owes can leave
+    let mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    let balance = spl_token::state::Account::unpack(&mint_vault.data.borrow())?.amount;
+    if args.amt > balance.saturating_sub(mint_ledger.outstanding) {
+        return Err(AppError::WithdrawalExceedsAvailable.into());
+    }
 
     let auth_bump = assert_tranfer_authority(program_id, mint_info, transfer_auth)?;
     let authority_seed = [
         program_id.as_ref(),
         mint_info.key.as_ref(),
-        "transfer_auth".as_bytes(),
+        TRANSFER_AUTH_SEED,
         &[auth_bump],
     ];
 
-
     spl_token_transfer(
         token_program_info.clone(),
         mint_vault.clone(),
@@ -44,6 +59,12 @@ pub fn process_clear(program_id: &Pubkey, accounts: &[AccountInfo], args: ClearA
         args.amt,
         &authority_seed
     )?;
-    
-    Ok(())
+
+    AppEvent::VaultCleared(VaultCleared {
+        authority: *signer_info.key,
+        mint: *mint_info.key,
+        destination: *token_account.key,
+        amount: args.amt,
+    })
+    .emit()
 }
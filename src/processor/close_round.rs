@@ -1,17 +1,15 @@
-use arrayref::array_ref;
 use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     msg,
-    keccak::hashv,
-    program_error::ProgramError,
-    pubkey::Pubkey
+    pubkey::Pubkey,
+    sysvar::{rent::Rent, Sysvar},
 };
 
-use crate::{ferror, state::*, utils::*};
+use crate::{error::AppError, event::*, pda::*, state::*, utils::*};
 
-pub fn process_close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+pub fn process_close(program_id: &Pubkey, accounts: &[AccountInfo], args: CloseRoundArgs) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let signer_info = next_account_info(account_info_iter)?;
     let config_info = next_account_info(account_info_iter)?;
@@ -19,80 +17,179 @@ pub fn process_close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRe
     let new_config_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
     let system_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
 
-    assert_signer(&signer_info)?;
-    assert_owned_by(config_info, &program_id)?;
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    assert_round(program_id, round_info)?;
     let mut config_data = ConfigureData::from_account_info(config_info)?;
-    assert_eq_pubkey(signer_info, &config_data.authority)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+    let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    let outstanding = config_data.outstanding_reward();
 
     let mut round_data = RoundData::from_account_info(round_info)?;
-    if config_data.authority != *signer_info.key {
-        return ferror!("invalid authority");
+    let is_authority = config_data.authority == *signer_info.key;
+    // once the sale has ended anyone can crank the close
+    let is_ended = config_data.end_time != 0 && now_timestamp() >= config_data.end_time;
+    if !is_authority && !is_ended {
+        return Err(AppError::InvalidAuthority.into());
     }
-    let mut total_allocated = 0;
-    if config_data.match1 > 0 {
-        total_allocated += config_data.total_reward * 2 / 100;
+    if config_data.closed {
+        return Err(AppError::RoundClosed.into());
     }
-    if config_data.match2 > 0 {
-        total_allocated += config_data.total_reward * 3 / 100;
-    }
-    if config_data.match3 > 0 {
-        total_allocated += config_data.total_reward * 5 / 100;
-    }
-    if config_data.match4 > 0 {
-        total_allocated += config_data.total_reward * 20 / 100;
-    }
-    if config_data.match5 > 0 {
-        total_allocated += config_data.total_reward * 30 / 100;
-    }
-    if config_data.match6 > 0 {
-        total_allocated += config_data.total_reward * 40 / 100;
-    }
-    
+
     let new_round = config_data.round + 1;
-    let bump = assert_config(&program_id, &new_config_info, new_round.to_string().clone())?;
-    if new_config_info.data_is_empty() {
-        create_or_allocate_account_raw(
-            *program_id,
-            new_config_info,
-            rent_info,
-            system_info,
-            signer_info,
-            ConfigureData::LEN,
-            &[
-                program_id.as_ref(),
-                "config".as_bytes(),
-                new_round.to_string().as_bytes(),
-                &[bump],
-            ],
-        )?;
+    let bump = assert_config(program_id, new_config_info, new_round)?;
+    // the next round only ever comes from this close, whatever sits at its address was not set up by it
+    if !new_config_info.data_is_empty() {
+        return Err(AppError::AlreadyInitialized.into());
     }
+    create_or_allocate_account_raw(
+        *program_id,
+        new_config_info,
+        rent_info,
+        system_info,
+        signer_info,
+        ConfigureData::LEN,
+        &[
+            program_id.as_ref(),
+            CONFIG_SEED,
+            new_round.to_string().as_bytes(),
+            &[bump],
+        ],
+    )?;
 
-    
+    // allocated is computed by Settle once the target is revealed
     config_data.closed = true;
-    config_data.allocated = total_allocated;
-    
-
-    let mut new_config_data = ConfigureData::from_account_info(config_info)?; 
-
-    let hash = hashv(&[&now_timestamp().to_be_bytes().as_slice(), &program_id.as_ref()]);
-    let kep_bytes = hash.to_bytes();
-    let bts = array_ref![kep_bytes, 0, 16];
-    let num = u128::from_be_bytes(*bts);
-    let fnum = (num % 1000000) as u64;
-    
-    new_config_data.target = fnum;
+    if config_data.is_pot_mode() {
+        config_data.total_reward += config_data.pot_collected;
+    }
+    match config_data.randomness_source {
+        RandomnessSource::CommitReveal | RandomnessSource::SlotHashes => {
+            config_data.draw_slot = now_slot() + DRAW_SLOT_DELAY;
+        }
+        RandomnessSource::Oracle => {
+            // bind a request that is still pending, so its value was unknown during the sale
+            let randomness_info = next_account_info(account_info_iter)?;
+            assert_owned_by(randomness_info, &config_data.oracle_program)?;
+            let randomness = OracleRandomness::from_account_info(randomness_info)?;
+            if randomness.authority != config_data.oracle_authority {
+                return Err(AppError::InvalidOracleAuthority.into());
+            }
+            if randomness.fulfilled {
+                return Err(AppError::RandomnessFulfilled.into());
+            }
+            config_data.randomness_account = *randomness_info.key;
+        }
+    }
+    // a migrated round whose tickets were all tallied when bought has no target left to wait for
+    if !config_data.revealed && config_data.total_shots > 0 && config_data.settled_shots == config_data.total_shots {
+        config_data.revealed = true;
+        config_data.finish_settlement(now_timestamp())?;
+    }
     config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+    mint_ledger.track(outstanding, &config_data)?;
 
+    let mut crank_reward = 0;
+    if is_ended && config_data.crank_reward > 0 {
+        // paid from lamports the authority deposited on top of the config rent
+        let rent = Rent::from_account_info(rent_info)?;
+        let spare = config_info
+            .lamports()
+            .saturating_sub(rent.minimum_balance(ConfigureData::LEN));
+        if spare >= config_data.crank_reward {
+            **config_info.try_borrow_mut_lamports()? -= config_data.crank_reward;
+            **signer_info.try_borrow_mut_lamports()? += config_data.crank_reward;
+            crank_reward = config_data.crank_reward;
+        } else {
+            msg!("crank reward not funded");
+        }
+    }
+
+    let now_ts = now_timestamp();
+    let mut new_config_data = ConfigureData::new();
+    if is_authority {
+        new_config_data.commitment = args.commitment;
+    }
+    new_config_data.randomness_source = config_data.randomness_source;
+    new_config_data.oracle_program = config_data.oracle_program;
+    new_config_data.max_batch = config_data.max_batch;
+    new_config_data.tiers = config_data.tiers;
+    new_config_data.revenue_shares = config_data.revenue_shares;
+    new_config_data.ticket_price = config_data.ticket_price;
+    new_config_data.payment_mint = config_data.payment_mint;
+    new_config_data.oracle_authority = config_data.oracle_authority;
+    if config_data.duration != 0 {
+        new_config_data.end_time = now_ts + config_data.duration;
+    }
+    new_config_data.duration = config_data.duration;
+    new_config_data.crank_reward = config_data.crank_reward;
+    new_config_data.claim_period = config_data.claim_period;
     new_config_data.authority = config_data.authority;
-    new_config_data.start_time = now_timestamp();
+    new_config_data.pending_authority = config_data.pending_authority;
+    new_config_data.start_time = now_ts;
     new_config_data.round = new_round;
-    new_config_data.total_reward = config_data.total_reward;
+    new_config_data.total_reward = 0;
+    new_config_data.carried_over = 0;
+    if !config_data.is_pot_mode() {
+        new_config_data.total_reward = config_data.total_reward.saturating_sub(config_data.carried_over);
+    }
+    new_config_data.pot_bp = config_data.pot_bp;
+    new_config_data.rollover_mode = config_data.rollover_mode;
+    new_config_data.referral_bp = config_data.referral_bp;
+    let mut rollover = 0;
+    if let Some(rollover_info) = account_info_iter.next() {
+        // winners are only known after settlement, so an earlier settled round is carried into the new one;
+        // that is the round opened by whichever close is handed this config, not necessarily the one right after it
+        assert_owned_by(rollover_info, program_id)?;
+        let mut rollover_data = ConfigureData::from_account_info(rollover_info)?;
+        assert_config(program_id, rollover_info, rollover_data.round)?;
+        if rollover_data.round >= config_data.round {
+            return Err(AppError::InvalidRolloverRound.into());
+        }
+        if !rollover_data.revealed || rollover_data.settled_shots != rollover_data.total_shots {
+            return Err(AppError::RoundNotSettled.into());
+        }
+        if rollover_data.rolled_over {
+            return Err(AppError::AlreadyRolledOver.into());
+        }
+        if rollover_data.token != config_data.token || rollover_data.is_pot_mode() != config_data.is_pot_mode() {
+            return Err(AppError::InvalidRolloverRound.into());
+        }
+        let rollover_outstanding = rollover_data.outstanding_reward();
+        rollover = rollover_data.unallocated_rollover();
+        msg!("rollover {} from round {}", rollover, rollover_data.round);
+        rollover_data.rolled_over = true;
+        rollover_data.rollover = rollover;
+        rollover_data.serialize(&mut &mut rollover_info.data.borrow_mut()[..])?;
+        mint_ledger.track(rollover_outstanding, &rollover_data)?;
+
+        new_config_data.total_reward += rollover;
+        new_config_data.carried_over = rollover;
+    }
     new_config_data.charge_addr = config_data.charge_addr;
+    new_config_data.token = config_data.token;
     new_config_data.serialize(&mut &mut new_config_info.data.borrow_mut()[..])?;
+    mint_ledger.track(0, &new_config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
 
-    round_data.round += 1;
+    round_data.round = new_round;
     round_data.serialize(&mut &mut round_info.data.borrow_mut()[..])?;
 
-    Ok(())
+    AppEvent::RoundClosed(RoundClosed {
+        round: config_data.round,
+        closer: *signer_info.key,
+        total_shots: config_data.total_shots,
+        total_reward: config_data.total_reward,
+        randomness_source: config_data.randomness_source,
+        draw_slot: config_data.draw_slot,
+        randomness_account: config_data.randomness_account,
+        crank_reward,
+        next_round: new_round,
+        next_total_reward: new_config_data.total_reward,
+        rollover,
+    })
+    .emit()
 }
@@ -0,0 +1,44 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_close_round_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if !config_data.closed || config_data.settled_shots != config_data.total_shots {
+        return Err(AppError::RoundNotSettled.into());
+    }
+    // every prize is either paid out or swept once the claim period is over
+    if config_data.claimed_winners < config_data.winners && !config_data.swept {
+        return Err(AppError::ClaimsPending.into());
+    }
+    if config_data.rollover_mode != RolloverMode::Disabled && !config_data.rolled_over {
+        return Err(AppError::RolloverPending.into());
+    }
+    // the revenue vault is shared between rounds, this round's share is only tracked here
+    if config_data.revenue_withdrawn < config_data.revenue {
+        return Err(AppError::RevenuePending.into());
+    }
+    if config_data.pot_surplus() > 0 && !config_data.surplus_withdrawn {
+        return Err(AppError::SurplusPending.into());
+    }
+    // CloseUserAccount checks against this config, so it has to outlive every user account
+    if config_data.open_user_accounts > 0 {
+        return Err(AppError::UsersPending.into());
+    }
+
+    close_account(config_info, signer_info)
+}
@@ -0,0 +1,55 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_close_user_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let user = next_account_info(account_info_iter)?;
+    let user_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    let round = config_data.round;
+    assert_config(program_id, config_info, round)?;
+    assert_user_info(program_id, user.key, user_info, round)?;
+    // unsettled tickets are still needed to finish the round tally
+    if !config_data.closed || config_data.settled_shots != config_data.total_shots {
+        return Err(AppError::RoundNotSettled.into());
+    }
+
+    // user_info may be gone already when the pages take more than one transaction
+    if !user_info.data_is_empty() {
+        assert_owned_by(user_info, program_id)?;
+        let user_data = UserData::from_account_info(user_info)?;
+        let expired = config_data.claim_deadline != 0 && now_timestamp() > config_data.claim_deadline;
+        if !user_data.claimed && !expired {
+            return Err(AppError::NotClaimed.into());
+        }
+        // anyone may clear out a user the round is done with, the rent goes back to the user who paid it
+        close_account(user_info, user)?;
+        config_data.open_user_accounts = config_data.open_user_accounts.saturating_sub(1);
+    }
+
+    // the remaining accounts are ticket pages of the user in this round
+    for page_info in account_info_iter {
+        assert_owned_by(page_info, program_id)?;
+        let page = TicketPage::from_account_info(page_info)?;
+        assert_ticket_page(program_id, user.key, page_info, round, page.page)?;
+        if !page.settled {
+            return Err(AppError::TicketsNotSettled.into());
+        }
+        close_account(page_info, user)?;
+        config_data.open_user_accounts = config_data.open_user_accounts.saturating_sub(1);
+    }
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
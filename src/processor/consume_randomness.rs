@@ -0,0 +1,46 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    keccak::hashv,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_consume_randomness(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let randomness_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+
+    if config_data.randomness_source != RandomnessSource::Oracle {
+        return Err(AppError::InvalidRandomnessSource.into());
+    }
+    if !config_data.closed {
+        return Err(AppError::SaleNotClosed.into());
+    }
+    if config_data.revealed {
+        return Err(AppError::TargetRevealed.into());
+    }
+    assert_eq_pubkey(randomness_info, &config_data.randomness_account)?;
+    assert_owned_by(randomness_info, &config_data.oracle_program)?;
+
+    let randomness = OracleRandomness::from_account_info(randomness_info)?;
+    if !randomness.fulfilled {
+        return Err(AppError::RandomnessNotFulfilled.into());
+    }
+
+    let hash = hashv(&[
+        &randomness.value,
+        config_info.key.as_ref(),
+        config_data.total_shots.to_be_bytes().as_slice(),
+    ]);
+    config_data.target = hash_to_number(&hash);
+    config_data.revealed = true;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
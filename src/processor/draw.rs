@@ -0,0 +1,68 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    keccak::hashv,
+    msg,
+    pubkey::Pubkey,
+    sysvar,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_draw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let slot_hashes_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(config_info, program_id)?;
+    assert_eq_pubkey(slot_hashes_info, &sysvar::slot_hashes::id())?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+
+    if config_data.randomness_source == RandomnessSource::Oracle {
+        return Err(AppError::InvalidRandomnessSource.into());
+    }
+    if !config_data.closed {
+        return Err(AppError::SaleNotClosed.into());
+    }
+    if config_data.revealed {
+        return Err(AppError::TargetRevealed.into());
+    }
+    let now_slot = now_slot();
+    if now_slot <= config_data.draw_slot {
+        return Err(AppError::DrawSlotNotReached.into());
+    }
+
+    let slot_hash = find_slot_hash(slot_hashes_info, config_data.draw_slot)?;
+    if config_data.randomness_source == RandomnessSource::CommitReveal {
+        if slot_hash.is_some() {
+            return Err(AppError::RevealWindowOpen.into());
+        }
+        // the authority did not reveal while the slot was in SlotHashes, the round is drawn without its secret
+        msg!("reveal window passed, drawing from slot hashes");
+        config_data.randomness_source = RandomnessSource::SlotHashes;
+        config_data.draw_slot = now_slot + DRAW_SLOT_DELAY;
+        config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+        return Ok(());
+    }
+
+    match slot_hash {
+        Some(slot_hash) => {
+            let hash = hashv(&[
+                &slot_hash,
+                config_info.key.as_ref(),
+                config_data.total_shots.to_be_bytes().as_slice(),
+            ]);
+            config_data.target = hash_to_number(&hash);
+            config_data.revealed = true;
+        }
+        None => {
+            // skipped slot or aged out of SlotHashes, draw again from a new future slot
+            msg!("slot {} not in SlotHashes", config_data.draw_slot);
+            config_data.draw_slot = now_slot + DRAW_SLOT_DELAY;
+        }
+    }
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    sysvar,
+};
+
+use crate::{error::AppError, pda::*, state::*, utils::*};
+
+pub fn process_initialize_program_state(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: GuardianArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    // only whoever deployed the program picks the first guardian
+    assert_upgrade_authority(program_id, program_data_info, signer_info.key)?;
+    assert_eq_pubkey(rent_info, &sysvar::rent::id())?;
+    assert_eq_pubkey(system_info, &solana_program::system_program::id())?;
+    let bump = assert_program_state(program_id, program_state_info)?;
+    // run once right after deploy, the guardian is rotated with SetGuardian
+    if !program_state_info.data_is_empty() {
+        return Err(AppError::AlreadyInitialized.into());
+    }
+    create_or_allocate_account_raw(
+        *program_id,
+        program_state_info,
+        rent_info,
+        system_info,
+        signer_info,
+        ProgramState::LEN,
+        &[program_id.as_ref(), PROGRAM_STATE_SEED, &[bump]],
+    )?;
+
+    let program_state = ProgramState {
+        guardian: args.guardian,
+        ..ProgramState::new()
+    };
+    program_state.serialize(&mut &mut program_state_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
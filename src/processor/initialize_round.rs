@@ -0,0 +1,235 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    sysvar,
+    pubkey::Pubkey
+};
+
+use crate::{error::AppError, pda::*, state::*, utils::*};
+
+pub fn process_initialize_round(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ConfigureArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_vault = next_account_info(account_info_iter)?;
+    let transfer_auth = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    let latest_config_info = next_account_info(account_info_iter)?;
+
+    assert_eq_pubkey(rent_info, &sysvar::rent::id())?;
+    assert_eq_pubkey(system_info, &solana_program::system_program::id())?;
+    assert_signer(signer_info)?;
+    // the round counter is shared, a round opened by anyone else could be taken over by the next close
+    assert_upgrade_authority(program_id, program_data_info, signer_info.key)?;
+    let round: u64 = args.round.parse().map_err(|_| ProgramError::InvalidArgument)?;
+    let bump = assert_config(program_id, config_info, round)?;
+
+    let round_bump = assert_round(program_id, round_info)?;
+    let mint_vault_bump = assert_mint_vault(program_id, mint_info, mint_vault)?;
+    let mint_vault_seed = [
+        program_id.as_ref(),
+        mint_info.key.as_ref(),
+        MINT_VAULT_SEED,
+        &[mint_vault_bump],
+    ];
+    let mint_ledger_bump = assert_mint_ledger(program_id, mint_info.key, mint_ledger_info)?;
+    let auth_bump = assert_tranfer_authority(program_id, mint_info, transfer_auth)?;
+    let authority_seed = [
+        program_id.as_ref(),
+        mint_info.key.as_ref(),
+        TRANSFER_AUTH_SEED,
+        &[auth_bump],
+    ];
+    if !config_info.data_is_empty() {
+        return Err(AppError::AlreadyInitialized.into());
+    }
+    create_or_allocate_account_raw(
+        *program_id,
+        config_info,
+        rent_info,
+        system_info,
+        signer_info,
+        ConfigureData::LEN,
+        &[
+            program_id.as_ref(),
+            CONFIG_SEED,
+            round.to_string().as_bytes(),
+            &[bump],
+        ],
+    )?;
+    // the mint vault is shared by every round paying out the same token
+    if mint_vault.data_is_empty() {
+        msg!("create mint vault");
+        spl_token_create_account(
+            token_program_info,
+            signer_info,
+            mint_info,
+            mint_vault,
+            transfer_auth,
+            &mint_vault_seed,
+            &authority_seed,
+            rent_info,
+        )?;
+    }
+    let mint_ledger_created = mint_ledger_info.data_is_empty();
+    if mint_ledger_created {
+        create_or_allocate_account_raw(
+            *program_id,
+            mint_ledger_info,
+            rent_info,
+            system_info,
+            signer_info,
+            MintLedger::LEN,
+            &[
+                program_id.as_ref(),
+                mint_info.key.as_ref(),
+                MINT_LEDGER_SEED,
+                &[mint_ledger_bump],
+            ],
+        )?;
+    }
+
+    let round_created = round_info.data_is_empty();
+    if round_created {
+        create_or_allocate_account_raw(
+            *program_id,
+            round_info,
+            rent_info,
+            system_info,
+            signer_info,
+            RoundData::LEN,
+            &[
+                program_id.as_ref(),
+                ROUND_SEED,
+                &[round_bump],
+            ],
+        )?;
+    }
+    if args.max_batch == 0 || args.max_batch > MAX_BATCH_SIZE {
+        return Err(AppError::InvalidMaxBatch.into());
+    }
+    if args.ticket_price == 0 {
+        return Err(AppError::InvalidTicketPrice.into());
+    }
+    // CloseRound binds the request by these, a default key leaves the round without a draw
+    if args.randomness_source == RandomnessSource::Oracle
+        && (args.oracle_program == Pubkey::default() || args.oracle_authority == Pubkey::default())
+    {
+        return Err(AppError::OracleNotSet.into());
+    }
+    if let Some(payment_mint) = args.payment_mint {
+        // ticket revenue is held by the transfer authority of the payment mint
+        let payment_mint_info = next_account_info(account_info_iter)?;
+        let revenue_vault = next_account_info(account_info_iter)?;
+        let revenue_auth = next_account_info(account_info_iter)?;
+        assert_eq_pubkey(payment_mint_info, &payment_mint)?;
+        let revenue_vault_bump = assert_revenue_vault(program_id, &payment_mint, revenue_vault)?;
+        assert_tranfer_authority(program_id, payment_mint_info, revenue_auth)?;
+        if revenue_vault.data_is_empty() {
+            msg!("create revenue vault");
+            spl_token_create_account(
+                token_program_info,
+                signer_info,
+                payment_mint_info,
+                revenue_vault,
+                revenue_auth,
+                &[
+                    program_id.as_ref(),
+                    payment_mint.as_ref(),
+                    REVENUE_VAULT_SEED,
+                    &[revenue_vault_bump],
+                ],
+                &[],
+                rent_info,
+            )?;
+        }
+    }
+    if args.end_time != 0 && args.end_time <= args.start_time {
+        return Err(AppError::InvalidEndTime.into());
+    }
+    assert_tiers(&args.tiers)?;
+    if args.pot_bp > 0 && args.payment_mint.is_some() {
+        return Err(AppError::PotRequiresSolPayments.into());
+    }
+    // the pot only holds what ticket sales paid in
+    if args.pot_bp > 0 && args.total_reward != 0 {
+        return Err(AppError::InvalidTotalReward.into());
+    }
+    let shares = args.revenue_shares.iter().map(|share| share.bp as u64).sum::<u64>();
+    if shares + args.pot_bp as u64 + args.referral_bp as u64 > MAX_BASIS_POINTS {
+        return Err(AppError::InvalidRevenueShares.into());
+    }
+    let mut round_data = if round_created {
+        RoundData::new()
+    } else {
+        RoundData::from_account_info(round_info)?
+    };
+    // rounds up to the current one exist or were closed, a closed config must not come back
+    if !round_created && round <= round_data.round {
+        return Err(AppError::InvalidRound.into());
+    }
+    // only the close of the latest round opens the one after it, a round opened past it would block that close
+    if !round_created {
+        assert_config(program_id, latest_config_info, round_data.round)?;
+        if !latest_config_info.data_is_empty() && !ConfigureData::from_account_info(latest_config_info)?.closed {
+            return Err(AppError::RoundOpen.into());
+        }
+    }
+    let mut config_data = ConfigureData::new();
+
+    config_data.commitment = args.commitment;
+    config_data.randomness_source = args.randomness_source;
+    config_data.oracle_program = args.oracle_program;
+    config_data.max_batch = args.max_batch;
+    config_data.tiers = args.tiers;
+    config_data.revenue_shares = args.revenue_shares;
+    config_data.pot_bp = args.pot_bp;
+    config_data.rollover_mode = args.rollover_mode;
+    config_data.referral_bp = args.referral_bp;
+    config_data.ticket_price = args.ticket_price;
+    config_data.payment_mint = args.payment_mint;
+    config_data.oracle_authority = args.oracle_authority;
+    config_data.end_time = args.end_time;
+    config_data.duration = args.end_time.saturating_sub(args.start_time);
+    config_data.crank_reward = args.crank_reward;
+    config_data.claim_period = args.claim_period;
+    config_data.authority = *signer_info.key;
+    if args.authority != *signer_info.key {
+        config_data.pending_authority = Some(args.authority);
+    }
+    config_data.start_time = args.start_time;
+    config_data.round = round;
+    config_data.total_reward = args.total_reward;
+    config_data.charge_addr = args.charge_addr;
+    config_data.token = *mint_info.key;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    let mut mint_ledger = if mint_ledger_created {
+        MintLedger {
+            mint: *mint_info.key,
+            ..MintLedger::new()
+        }
+    } else {
+        MintLedger::from_account_info(mint_ledger_info)?
+    };
+    mint_ledger.track(0, &config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+
+    round_data.round = round;
+    round_data.serialize(&mut &mut round_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
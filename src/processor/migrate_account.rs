@@ -0,0 +1,150 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::{error::AppError, pda::*, state::*, utils::*};
+
+pub fn process_migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: MigrateAccountArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let account_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(account_info, program_id)?;
+    assert_eq_pubkey(system_info, &solana_program::system_program::id())?;
+
+    let (_, _, len) = args.kind.layout();
+    if account_info.data_len() == len {
+        return Err(AppError::AlreadyMigrated.into());
+    }
+    let mut migrated_config = None;
+    // the account has to sit at the address of the kind it claims, and its round authority signs
+    let (authority, migrated) = match args.kind {
+        AccountKind::Config => {
+            let legacy = LegacyConfigureData::from_account_info(account_info)?;
+            assert_config(program_id, account_info, legacy.round)?;
+            assert_eq_pubkey(config_info, account_info.key)?;
+            let authority = legacy.authority;
+            let config_data = ConfigureData::from(legacy);
+            let migrated = config_data.try_to_vec()?;
+            migrated_config = Some(config_data);
+            (authority, migrated)
+        }
+        AccountKind::User => {
+            let user = next_account_info(account_info_iter)?;
+            let legacy = LegacyUserData::from_account_info(account_info)?;
+            assert_user_info(program_id, user.key, account_info, legacy.round)?;
+            assert_owned_by(config_info, program_id)?;
+            assert_config(program_id, config_info, legacy.round)?;
+            let mut config_data = ConfigureData::from_account_info(config_info)?;
+            let outstanding = config_data.outstanding_reward();
+            config_data.open_user_accounts += 1;
+            let user_data = UserData::from(legacy);
+            config_data.winners += user_data.is_winner() as u64;
+            // the first release paid claims without counting them on the config, nor in any ledger
+            if user_data.claimed {
+                config_data.claimed = config_data
+                    .claimed
+                    .checked_add(user_data.reward)
+                    .ok_or(AppError::CheckedCalculateFailed)?;
+                config_data.claimed_winners += user_data.is_winner() as u64;
+            }
+            config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+            let mint_ledger_info = next_account_info(account_info_iter)?;
+            assert_owned_by(mint_ledger_info, program_id)?;
+            assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+            let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+            mint_ledger.track(outstanding, &config_data)?;
+            mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+            (config_data.authority, user_data.try_to_vec()?)
+        }
+        AccountKind::Round => {
+            assert_round(program_id, account_info)?;
+            if account_info.data_len() != RoundData::LEN - ACCOUNT_HEADER_LEN {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let round: u64 = try_from_slice_unchecked(&account_info.data.borrow())?;
+            assert_owned_by(config_info, program_id)?;
+            assert_config(program_id, config_info, round)?;
+            let config_data = ConfigureData::from_account_info(config_info)?;
+            let round_data = RoundData {
+                round,
+                ..RoundData::new()
+            };
+            (config_data.authority, round_data.try_to_vec()?)
+        }
+        // written with a header from their first release
+        AccountKind::TicketPage | AccountKind::ReferrerStats | AccountKind::ProgramState | AccountKind::MintLedger => {
+            return Err(ProgramError::InvalidArgument);
+        }
+    };
+    if authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if let Some(config_data) = migrated_config {
+        // the first release kept no ledger, the shared vault still has to hold what the round owes
+        let mint_ledger_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let mint_ledger_bump = assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+        let mut mint_ledger = if mint_ledger_info.data_is_empty() {
+            create_or_allocate_account_raw(
+                *program_id,
+                mint_ledger_info,
+                rent_info,
+                system_info,
+                signer_info,
+                MintLedger::LEN,
+                &[
+                    program_id.as_ref(),
+                    config_data.token.as_ref(),
+                    MINT_LEDGER_SEED,
+                    &[mint_ledger_bump],
+                ],
+            )?;
+            MintLedger {
+                mint: config_data.token,
+                ..MintLedger::new()
+            }
+        } else {
+            assert_owned_by(mint_ledger_info, program_id)?;
+            MintLedger::from_account_info(mint_ledger_info)?
+        };
+        mint_ledger.track(0, &config_data)?;
+        mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+    }
+
+    let required_lamports = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(signer_info.key, account_info.key, required_lamports),
+            &[
+                signer_info.clone(),
+                account_info.clone(),
+                system_info.clone(),
+            ],
+        )?;
+    }
+    account_info.realloc(len, false)?;
+
+    let mut data = account_info.data.borrow_mut();
+    data.fill(0);
+    data[..migrated.len()].copy_from_slice(&migrated);
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_propose_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: ProposeAuthorityArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+
+    config_data.pending_authority = Some(args.new_authority);
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
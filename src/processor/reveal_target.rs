@@ -0,0 +1,66 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    keccak::hashv,
+    pubkey::Pubkey,
+    sysvar,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_reveal_target(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: RevealTargetArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let slot_hashes_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    assert_eq_pubkey(slot_hashes_info, &sysvar::slot_hashes::id())?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_eq_pubkey(signer_info, &config_data.authority)?;
+
+    if config_data.randomness_source != RandomnessSource::CommitReveal {
+        return Err(AppError::InvalidRandomnessSource.into());
+    }
+    if !config_data.closed {
+        return Err(AppError::SaleNotClosed.into());
+    }
+    if config_data.revealed {
+        return Err(AppError::TargetRevealed.into());
+    }
+    if hashv(&[&args.secret]).to_bytes() != config_data.commitment {
+        return Err(AppError::InvalidSecret.into());
+    }
+
+    let now_slot = now_slot();
+    if now_slot <= config_data.draw_slot {
+        return Err(AppError::DrawSlotNotReached.into());
+    }
+
+    // the secret alone is known to the authority, who can buy and close at any time,
+    // so the target also takes the hash of a slot produced after the close
+    match find_slot_hash(slot_hashes_info, config_data.draw_slot)? {
+        Some(slot_hash) => {
+            let hash = hashv(&[
+                &args.secret,
+                &slot_hash,
+                config_info.key.as_ref(),
+                config_data.total_shots.to_be_bytes().as_slice(),
+            ]);
+            config_data.target = hash_to_number(&hash);
+            config_data.revealed = true;
+        }
+        // a new slot would hand the authority, who saw the target at this one, another pick;
+        // the round is left to Draw, which falls back to a slot without the secret
+        None => return Err(AppError::RevealWindowPassed.into()),
+    }
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
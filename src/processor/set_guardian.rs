@@ -0,0 +1,31 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_set_guardian(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: GuardianArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(program_state_info, program_id)?;
+    assert_program_state(program_id, program_state_info)?;
+    let mut program_state = ProgramState::from_account_info(program_state_info)?;
+    if program_state.guardian != *signer_info.key {
+        return Err(AppError::InvalidGuardian.into());
+    }
+
+    program_state.guardian = args.guardian;
+    program_state.serialize(&mut &mut program_state_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
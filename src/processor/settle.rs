@@ -0,0 +1,95 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_settle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let user = next_account_info(account_info_iter)?;
+    let user_info = next_account_info(account_info_iter)?;
+    let page_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
+
+    assert_owned_by(config_info, program_id)?;
+    assert_owned_by(user_info, program_id)?;
+    assert_owned_by(page_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    let round = config_data.round;
+    assert_config(program_id, config_info, round)?;
+    assert_user_info(program_id, user.key, user_info, round)?;
+    let mut page = TicketPage::from_account_info(page_info)?;
+    assert_ticket_page(program_id, user.key, page_info, round, page.page)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+    let outstanding = config_data.outstanding_reward();
+
+    if !config_data.revealed {
+        return Err(AppError::TargetNotRevealed.into());
+    }
+    if page.settled {
+        return Err(AppError::PageSettled.into());
+    }
+    let mut user_data = UserData::from_account_info(user_info)?;
+    let was_winner = user_data.is_winner();
+
+    let target_array = number_to_digits(config_data.target);
+    let mut page_shots = 0;
+    for TicketEntry { shot, num } in page.tickets.iter() {
+        page_shots += num;
+        let matched = count_matching_elements_until_difference(shot, &target_array) as u8;
+        match matched {
+            1 => {
+                user_data.match1 += num;
+                config_data.match1 += num;
+            }
+            2 => {
+                user_data.match2 += num;
+                config_data.match2 += num;
+            }
+            3 => {
+                user_data.match3 += num;
+                config_data.match3 += num;
+            }
+            4 => {
+                user_data.match4 += num;
+                config_data.match4 += num;
+            }
+            5 => {
+                user_data.match5 += num;
+                config_data.match5 += num;
+            }
+            6 => {
+                user_data.match6 += num;
+                config_data.match6 += num;
+            }
+
+            _ => {}
+        }
+    }
+    page.settled = true;
+    page.serialize(&mut &mut page_info.data.borrow_mut()[..])?;
+
+    user_data.settled_pages += 1;
+    if !was_winner && user_data.is_winner() {
+        config_data.winners += 1;
+    }
+    user_data.serialize(&mut &mut user_info.data.borrow_mut()[..])?;
+
+    config_data.settled_shots += page_shots;
+    if config_data.settled_shots == config_data.total_shots {
+        config_data.finish_settlement(now_timestamp())?;
+    }
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    // the pool drops to what the winners are owed once the last ticket is counted
+    let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    mint_ledger.track(outstanding, &config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, pda::*, state::*, utils::*};
+
+pub fn process_sweep_unclaimed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SweepUnclaimedArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_vault = next_account_info(account_info_iter)?;
+    let transfer_auth = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
+    let round_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    // winners cannot claim while paused, so their rewards cannot be swept either
+    assert_claims_not_paused(program_id, program_state_info)?;
+    assert_owned_by(config_info, program_id)?;
+    assert_eq_pubkey(token_program_info, &spl_token::id())?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+    let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    let outstanding = config_data.outstanding_reward();
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if config_data.claim_deadline == 0 || now_timestamp() <= config_data.claim_deadline {
+        return Err(AppError::ClaimPeriodNotOver.into());
+    }
+    if config_data.settled_shots != config_data.total_shots {
+        return Err(AppError::RoundNotSettled.into());
+    }
+    if config_data.swept {
+        return Err(AppError::AlreadySwept.into());
+    }
+    let unclaimed = config_data
+        .allocated
+        .checked_sub(config_data.claimed)
+        .ok_or(AppError::CheckedCalculateFailed)?;
+
+    match args.destination {
+        SweepDestination::Treasury if config_data.is_pot_mode() => {
+            let prize_pot = next_account_info(account_info_iter)?;
+            assert_owned_by(prize_pot, program_id)?;
+            assert_prize_pot(program_id, prize_pot)?;
+            withdraw_lamports(prize_pot, destination_info, unclaimed)?;
+        }
+        SweepDestination::Treasury => {
+            assert_eq_pubkey(mint_info, &config_data.token)?;
+            assert_mint_vault(program_id, mint_info, mint_vault)?;
+            let auth_bump = assert_tranfer_authority(program_id, mint_info, transfer_auth)?;
+            spl_token_transfer(
+                token_program_info.clone(),
+                mint_vault.clone(),
+                destination_info.clone(),
+                transfer_auth.clone(),
+                unclaimed,
+                &[
+                    program_id.as_ref(),
+                    mint_info.key.as_ref(),
+                    TRANSFER_AUTH_SEED,
+                    &[auth_bump],
+                ],
+            )?;
+        }
+        SweepDestination::NextRound => {
+            // the claim window opens at settlement, after the round that followed this one has
+            // usually closed too, so the pool goes to whichever round is open now
+            assert_round(program_id, round_info)?;
+            let round_data = RoundData::from_account_info(round_info)?;
+            if round_data.round <= config_data.round {
+                return Err(AppError::InvalidRound.into());
+            }
+            // the vault and the pot are shared by all rounds, so only the pool accounting moves
+            assert_owned_by(destination_info, program_id)?;
+            assert_config(program_id, destination_info, round_data.round)?;
+            let mut next_config_data = ConfigureData::from_account_info(destination_info)?;
+            let next_outstanding = next_config_data.outstanding_reward();
+            if next_config_data.closed {
+                return Err(AppError::RoundClosed.into());
+            }
+            if next_config_data.token != config_data.token {
+                return Err(AppError::InvalidToken.into());
+            }
+            next_config_data.total_reward = next_config_data
+                .total_reward
+                .checked_add(unclaimed)
+                .ok_or(AppError::CheckedCalculateFailed)?;
+            next_config_data.carried_over += unclaimed;
+            next_config_data.serialize(&mut &mut destination_info.data.borrow_mut()[..])?;
+            mint_ledger.track(next_outstanding, &next_config_data)?;
+        }
+    }
+    msg!("swept {}", unclaimed);
+
+    config_data.swept = true;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+    mint_ledger.track(outstanding, &config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateConfigArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let mint_ledger_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    assert_owned_by(mint_ledger_info, program_id)?;
+    assert_mint_ledger(program_id, &config_data.token, mint_ledger_info)?;
+    let outstanding = config_data.outstanding_reward();
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if config_data.closed {
+        return Err(AppError::RoundClosed.into());
+    }
+    let sold = config_data.total_shots > 0;
+
+    // players bought under these terms, so they can only change in their favour
+    if let Some(charge_addr) = args.charge_addr {
+        if sold {
+            return Err(AppError::TicketsSold.into());
+        }
+        config_data.charge_addr = charge_addr;
+    }
+    if let Some(total_reward) = args.total_reward {
+        if config_data.is_pot_mode() {
+            return Err(AppError::InvalidTotalReward.into());
+        }
+        if sold && total_reward < config_data.total_reward {
+            return Err(AppError::TicketsSold.into());
+        }
+        config_data.total_reward = total_reward;
+    }
+    if let Some(start_time) = args.start_time {
+        if sold {
+            return Err(AppError::TicketsSold.into());
+        }
+        config_data.start_time = start_time;
+    }
+    if let Some(end_time) = args.end_time {
+        if end_time != 0 && end_time <= now_timestamp() {
+            return Err(AppError::InvalidEndTime.into());
+        }
+        let shortened = end_time != 0 && (config_data.end_time == 0 || end_time < config_data.end_time);
+        if sold && shortened {
+            return Err(AppError::TicketsSold.into());
+        }
+        config_data.end_time = end_time;
+    }
+    if config_data.end_time != 0 && config_data.end_time <= config_data.start_time {
+        return Err(AppError::InvalidEndTime.into());
+    }
+    config_data.duration = config_data.end_time.saturating_sub(config_data.start_time);
+    if let Some(max_batch) = args.max_batch {
+        if max_batch == 0 || max_batch > MAX_BATCH_SIZE {
+            return Err(AppError::InvalidMaxBatch.into());
+        }
+        config_data.max_batch = max_batch;
+    }
+    if let Some(ticket_price) = args.ticket_price {
+        if sold {
+            return Err(AppError::TicketsSold.into());
+        }
+        if ticket_price == 0 {
+            return Err(AppError::InvalidTicketPrice.into());
+        }
+        config_data.ticket_price = ticket_price;
+    }
+    if let Some(tiers) = args.tiers {
+        if sold {
+            return Err(AppError::TicketsSold.into());
+        }
+        assert_tiers(&tiers)?;
+        config_data.tiers = tiers;
+    }
+    if let Some(commitment) = args.commitment {
+        // replacing a commitment would let the authority pick another target
+        if config_data.commitment != [0; 32] {
+            return Err(AppError::CommitmentAlreadySet.into());
+        }
+        config_data.commitment = commitment;
+    }
+    if let Some(crank_reward) = args.crank_reward {
+        config_data.crank_reward = crank_reward;
+    }
+    if let Some(claim_period) = args.claim_period {
+        // winners lose whatever is swept after the deadline, 0 never sweeps
+        let shortened = claim_period != 0 && (config_data.claim_period == 0 || claim_period < config_data.claim_period);
+        if sold && shortened {
+            return Err(AppError::TicketsSold.into());
+        }
+        config_data.claim_period = claim_period;
+    }
+    if let Some(revenue_shares) = args.revenue_shares {
+        config_data.revenue_shares = revenue_shares;
+    }
+    if let Some(referral_bp) = args.referral_bp {
+        config_data.referral_bp = referral_bp;
+    }
+    let shares = config_data.active_revenue_shares().map(|share| share.bp as u64).sum::<u64>();
+    if shares + config_data.pot_bp as u64 + config_data.referral_bp as u64 > MAX_BASIS_POINTS {
+        return Err(AppError::InvalidRevenueShares.into());
+    }
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    let mut mint_ledger = MintLedger::from_account_info(mint_ledger_info)?;
+    mint_ledger.track(outstanding, &config_data)?;
+    mint_ledger.serialize(&mut &mut mint_ledger_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
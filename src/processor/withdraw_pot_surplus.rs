@@ -0,0 +1,47 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, state::*, utils::*};
+
+pub fn process_withdraw_pot_surplus(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let prize_pot = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_claims_not_paused(program_id, program_state_info)?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if !config_data.revealed || config_data.settled_shots != config_data.total_shots {
+        return Err(AppError::RoundNotSettled.into());
+    }
+    if config_data.surplus_withdrawn {
+        return Err(AppError::NoPotSurplus.into());
+    }
+    // tiers nobody won outside the rollover mode and the share outside the tier sum are owed to no one
+    let surplus = config_data.pot_surplus();
+    if surplus == 0 {
+        return Err(AppError::NoPotSurplus.into());
+    }
+    assert_owned_by(prize_pot, program_id)?;
+    assert_prize_pot(program_id, prize_pot)?;
+    withdraw_lamports(prize_pot, destination_info, surplus)?;
+    msg!("pot surplus {}", surplus);
+
+    config_data.surplus_withdrawn = true;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    Ok(())
+}
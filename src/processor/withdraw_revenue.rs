@@ -0,0 +1,60 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{error::AppError, pda::*, state::*, utils::*};
+
+pub fn process_withdraw_revenue(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: WithdrawRevenueArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let payment_mint_info = next_account_info(account_info_iter)?;
+    let revenue_vault = next_account_info(account_info_iter)?;
+    let revenue_auth = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let program_state_info = next_account_info(account_info_iter)?;
+
+    assert_signer(signer_info)?;
+    assert_claims_not_paused(program_id, program_state_info)?;
+    assert_eq_pubkey(token_program_info, &spl_token::id())?;
+    assert_owned_by(config_info, program_id)?;
+    let mut config_data = ConfigureData::from_account_info(config_info)?;
+    assert_config(program_id, config_info, config_data.round)?;
+    if config_data.authority != *signer_info.key {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    if config_data.payment_mint != Some(*payment_mint_info.key) {
+        return Err(AppError::InvalidToken.into());
+    }
+    assert_revenue_vault(program_id, payment_mint_info.key, revenue_vault)?;
+    let auth_bump = assert_tranfer_authority(program_id, payment_mint_info, revenue_auth)?;
+
+    // the vault is shared by every round paid in the same mint, only this round's revenue can leave
+    if args.amount > config_data.revenue - config_data.revenue_withdrawn {
+        return Err(AppError::WithdrawalExceedsAvailable.into());
+    }
+    config_data.revenue_withdrawn += args.amount;
+    config_data.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    spl_token_transfer(
+        token_program_info.clone(),
+        revenue_vault.clone(),
+        token_account.clone(),
+        revenue_auth.clone(),
+        args.amount,
+        &[
+            program_id.as_ref(),
+            payment_mint_info.key.as_ref(),
+            TRANSFER_AUTH_SEED,
+            &[auth_bump],
+        ],
+    )
+}
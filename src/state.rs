@@ -1,25 +1,305 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::AccountInfo,
-    borsh0_10::try_from_slice_unchecked,
     program_error::ProgramError,
     pubkey::Pubkey,
 };
+use crate::{error::AppError, utils::try_from_slice_unchecked};
+pub use oracle_interface::OracleRandomness;
 use std::collections::HashMap;
+
+/// discriminator and layout version in front of every program account
+pub const ACCOUNT_HEADER_LEN: usize = 8 + 1;
+
+/// slots between CloseRound and the slot whose hash draws the target
+pub const DRAW_SLOT_DELAY: u64 = 16;
+
+/// upper bound for ConfigureData.max_batch, keeps a quick-pick buy within the compute budget
+pub const MAX_BATCH_SIZE: u64 = 100;
+
+/// basis points in a whole prize pool
+pub const MAX_BASIS_POINTS: u64 = 10_000;
+
+/// the original 2/3/5/20/30/40 percent split, match1 to match6
+pub const DEFAULT_TIERS: [u16; 6] = [200, 300, 500, 2000, 3000, 4000];
+
+/// entries per TicketPage, at least MAX_BATCH_SIZE so a buy spills into one new page at most
+pub const TICKETS_PER_PAGE: usize = 100;
+
+/// revenue recipients paid directly by Buy, on top of charge_addr
+pub const MAX_REVENUE_RECIPIENTS: usize = 4;
+
+/// Rejects an account of another type, or one written with a layout MigrateAccount must upgrade.
+fn check_header(discriminator: &[u8; 8], version: u8, expected: &[u8; 8], expected_version: u8) -> Result<(), ProgramError> {
+    if discriminator != expected {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if version != expected_version {
+        return Err(AppError::InvalidAccountVersion.into());
+    }
+    Ok(())
+}
+
+/// Program accounts MigrateAccount can upgrade.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AccountKind {
+    Config,
+    User,
+    TicketPage,
+    Round,
+    ReferrerStats,
+    ProgramState,
+    MintLedger,
+}
+
+impl AccountKind {
+    /// Discriminator, version and length of the current layout.
+    pub fn layout(&self) -> ([u8; 8], u8, usize) {
+        match self {
+            AccountKind::Config => (ConfigureData::DISCRIMINATOR, ConfigureData::VERSION, ConfigureData::LEN),
+            AccountKind::User => (UserData::DISCRIMINATOR, UserData::VERSION, UserData::LEN),
+            AccountKind::TicketPage => (TicketPage::DISCRIMINATOR, TicketPage::VERSION, TicketPage::LEN),
+            AccountKind::Round => (RoundData::DISCRIMINATOR, RoundData::VERSION, RoundData::LEN),
+            AccountKind::ReferrerStats => (ReferrerStats::DISCRIMINATOR, ReferrerStats::VERSION, ReferrerStats::LEN),
+            AccountKind::ProgramState => (ProgramState::DISCRIMINATOR, ProgramState::VERSION, ProgramState::LEN),
+            AccountKind::MintLedger => (MintLedger::DISCRIMINATOR, MintLedger::VERSION, MintLedger::LEN),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct MigrateAccountArgs {
+    /// checked against the address the account is derived at
+    pub kind: AccountKind,
+}
+
+/// ConfigureData as the first release wrote it, without a header.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct LegacyConfigureData {
+    pub authority: Pubkey,
+    pub charge_addr: Pubkey,
+    pub token: Pubkey,
+    pub round: u64,
+    pub total_reward: u64,
+    pub allocated: u64,
+    pub target: u64,
+    pub start_time: u64,
+    pub total_shots: u64,
+    pub match1: u64,
+    pub match2: u64,
+    pub match3: u64,
+    pub match4: u64,
+    pub match5: u64,
+    pub match6: u64,
+    pub closed: bool,
+}
+
+impl LegacyConfigureData {
+    pub const LEN: usize = 32 * 3 + 32 + 8 * 12 + 1;
+    /// lamports per ticket, fixed in the first release
+    pub const TICKET_PRICE: u64 = 50_000_000;
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<LegacyConfigureData, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        try_from_slice_unchecked(&a.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl From<LegacyConfigureData> for ConfigureData {
+    /// Every buy was tallied on the spot, so the legacy tickets come over settled;
+    /// a closed round also comes out revealed with the allocation of its close.
+    fn from(legacy: LegacyConfigureData) -> Self {
+        ConfigureData {
+            authority: legacy.authority,
+            charge_addr: legacy.charge_addr,
+            token: legacy.token,
+            round: legacy.round,
+            total_reward: legacy.total_reward,
+            allocated: legacy.allocated,
+            target: legacy.target,
+            start_time: legacy.start_time,
+            total_shots: legacy.total_shots,
+            match1: legacy.match1,
+            match2: legacy.match2,
+            match3: legacy.match3,
+            match4: legacy.match4,
+            match5: legacy.match5,
+            match6: legacy.match6,
+            closed: legacy.closed,
+            revealed: legacy.closed,
+            settled_shots: legacy.total_shots,
+            max_batch: MAX_BATCH_SIZE,
+            tiers: DEFAULT_TIERS,
+            ticket_price: LegacyConfigureData::TICKET_PRICE,
+            ..ConfigureData::new()
+        }
+    }
+}
+
+/// UserData as the first release wrote it, shots kept in a map.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct LegacyUserData {
+    pub shots: HashMap<[u8; 6], u64>,
+    pub total_shots: u64,
+    pub round: u64,
+    pub reward: u64,
+    pub claimed: bool,
+    pub match1: u64,
+    pub match2: u64,
+    pub match3: u64,
+    pub match4: u64,
+    pub match5: u64,
+    pub match6: u64,
+}
+
+impl LegacyUserData {
+    pub const LEN: usize = 200 + 1 + 8 * 9;
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<LegacyUserData, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        try_from_slice_unchecked(&a.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl From<LegacyUserData> for UserData {
+    /// The shots were matched against the target when bought, so only the tallies move over
+    /// and the user has no ticket pages to settle.
+    fn from(legacy: LegacyUserData) -> Self {
+        UserData {
+            total_shots: legacy.total_shots,
+            round: legacy.round,
+            reward: legacy.reward,
+            claimed: legacy.claimed,
+            match1: legacy.match1,
+            match2: legacy.match2,
+            match3: legacy.match3,
+            match4: legacy.match4,
+            match5: legacy.match5,
+            match6: legacy.match6,
+            ..UserData::new()
+        }
+    }
+}
+
+/// Share of every ticket payment sent to one recipient, unused when bp is 0.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RevenueShare {
+    /// wallet in SOL mode, token account of payment_mint otherwise
+    pub recipient: Pubkey,
+    pub bp: u16,
+}
+
+impl RevenueShare {
+    pub const LEN: usize = 32 + 2;
+
+    pub fn amount(&self, payment: u64) -> u64 {
+        (payment as u128 * self.bp as u128 / MAX_BASIS_POINTS as u128) as u64
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RandomnessSource {
+    /// authority reveals a secret committed when the round opened, hashed with a slot recorded by CloseRound;
+    /// once that slot leaves SlotHashes unrevealed, Draw turns the round into a SlotHashes one
+    #[default]
+    CommitReveal,
+    /// anyone draws from SlotHashes at a slot recorded by CloseRound
+    SlotHashes,
+    /// anyone consumes a randomness account fulfilled by the configured oracle
+    Oracle,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RolloverMode {
+    /// tiers without winners stay in the vault, in pot mode WithdrawPotSurplus takes them out
+    #[default]
+    Disabled,
+    /// every tier without winners carries into a later round, whichever CloseRound is handed this config
+    AllTiers,
+    /// only the match6 tier carries over, when nobody hit it
+    TopTier,
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct ConfigureArgs {
-    /// Contract admin
+    /// Contract admin, proposed as pending authority when it is not the signer
     pub authority: Pubkey,
     pub charge_addr: Pubkey,
     pub round: String,
     pub start_time: u64,
     pub total_reward: u64,
+    /// keccak(secret) committed before the sale, opened by RevealTarget
+    pub commitment: [u8; 32],
+    pub randomness_source: RandomnessSource,
+    /// program owning the randomness accounts in Oracle mode
+    pub oracle_program: Pubkey,
+    pub max_batch: u64,
+    /// share of total_reward in basis points per match level, match1 to match6, never decreasing
+    pub tiers: [u16; 6],
+    /// price of one ticket, in lamports or in payment_mint base units
+    pub ticket_price: u64,
+    /// SPL token tickets are paid in, SOL when None
+    pub payment_mint: Option<Pubkey>,
+    /// key that must fulfill the randomness account in Oracle mode
+    pub oracle_authority: Pubkey,
+    /// sale end, 0 leaves the round open until the authority closes it
+    pub end_time: u64,
+    /// lamports paid from the config account to whoever closes the round after end_time
+    pub crank_reward: u64,
+    /// seconds after the last ticket is settled during which winners can claim, 0 for no deadline
+    pub claim_period: u64,
+    /// the rest of each payment goes to charge_addr, or the revenue vault for SPL payments
+    pub revenue_shares: [RevenueShare; MAX_REVENUE_RECIPIENTS],
+    /// share of each SOL payment added to the prize pot, prizes are paid in lamports when set
+    pub pot_bp: u16,
+    pub rollover_mode: RolloverMode,
+    /// share of each payment sent to the buyer's referrer
+    pub referral_bp: u16,
+}
+
+/// Fields UpdateConfig may change on an open round, None keeps the current value
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct UpdateConfigArgs {
+    /// only before the first ticket is sold
+    pub charge_addr: Option<Pubkey>,
+    /// not in pot mode, where the pool is what ticket sales paid in, and never lowered once tickets are sold
+    pub total_reward: Option<u64>,
+    /// only before the first ticket is sold
+    pub start_time: Option<u64>,
+    /// only extended once tickets are sold
+    pub end_time: Option<u64>,
+    pub max_batch: Option<u64>,
+    /// only before the first ticket is sold
+    pub ticket_price: Option<u64>,
+    /// only before the first ticket is sold
+    pub tiers: Option<[u16; 6]>,
+    /// only while no commitment is set
+    pub commitment: Option<[u8; 32]>,
+    pub crank_reward: Option<u64>,
+    /// only extended once tickets are sold
+    pub claim_period: Option<u64>,
+    pub revenue_shares: Option<[RevenueShare; MAX_REVENUE_RECIPIENTS]>,
+    pub referral_bp: Option<u16>,
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct ConfigureData {
+    pub discriminator: [u8; 8],
+    pub version: u8,
     /// Contract admin
     pub authority: Pubkey,
     pub charge_addr: Pubkey,
@@ -37,16 +317,167 @@ pub struct ConfigureData {
     pub match5: u64,
     pub match6: u64,
     pub closed: bool,
+    pub commitment: [u8; 32],
+    /// target is set, by RevealTarget or Draw
+    pub revealed: bool,
+    /// tickets already checked against the target by Settle
+    pub settled_shots: u64,
+    pub randomness_source: RandomnessSource,
+    /// slot whose hash draws the target, with the secret in CommitReveal mode, set by CloseRound
+    pub draw_slot: u64,
+    pub oracle_program: Pubkey,
+    /// randomness account bound to the round by CloseRound in Oracle mode
+    pub randomness_account: Pubkey,
+    /// most tickets a single buy can take
+    pub max_batch: u64,
+    /// share of total_reward in basis points per match level, match1 to match6
+    pub tiers: [u16; 6],
+    pub ticket_price: u64,
+    /// SPL token tickets are paid in into the revenue vault, SOL to charge_addr when None
+    pub payment_mint: Option<Pubkey>,
+    pub oracle_authority: Pubkey,
+    /// after end_time buys are rejected and anyone may close the round
+    pub end_time: u64,
+    /// sale length carried over to the rounds opened by CloseRound
+    pub duration: u64,
+    pub crank_reward: u64,
+    pub claim_period: u64,
+    /// set once Settle has counted every ticket, claims are rejected after it and the rest can be swept
+    pub claim_deadline: u64,
+    /// reward already paid out by Claim
+    pub claimed: u64,
+    pub swept: bool,
+    /// proposed admin, takes over once it signs AcceptAuthority
+    pub pending_authority: Option<Pubkey>,
+    pub revenue_shares: [RevenueShare; MAX_REVENUE_RECIPIENTS],
+    pub pot_bp: u16,
+    /// lamports this round's buys added to the prize pot, moved into total_reward by CloseRound
+    pub pot_collected: u64,
+    pub rollover_mode: RolloverMode,
+    /// reward added on top by rollovers and sweeps, not copied on to the next round
+    pub carried_over: u64,
+    /// set once CloseRound has carried this round's unallocated tiers forward, into the round it opened then
+    pub rolled_over: bool,
+    /// amount carried forward, recorded by CloseRound
+    pub rollover: u64,
+    pub referral_bp: u16,
+    /// payment_mint units this round's buys left in the revenue vault
+    pub revenue: u64,
+    /// revenue already taken out by WithdrawRevenue
+    pub revenue_withdrawn: u64,
+    /// set once WithdrawPotSurplus has taken the pot lamports nobody is owed
+    pub surplus_withdrawn: bool,
+    /// user infos and ticket pages created by buys and not yet closed by CloseUserAccount
+    pub open_user_accounts: u64,
+    /// users holding a winning ticket, counted by Settle
+    pub winners: u64,
+    /// winners paid out by Claim, the split leaves rounding dust so claimed alone never reaches allocated
+    pub claimed_winners: u64,
 }
 
 impl ConfigureData {
-    pub const LEN: usize = 32 * 3 + 32 + 8 * 12  + 1;
+    pub const DISCRIMINATOR: [u8; 8] = *b"config\0\0";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 * 3 + 32 + 8 * 12 + 1 + 32 + 1 + 8 + 1 + 8 + 32 * 2 + 8 + 2 * 6 + 8 + 33 + 32 + 8 * 3 + 8 * 3 + 1 + 33
+        + RevenueShare::LEN * MAX_REVENUE_RECIPIENTS + 2 + 8 + 1 + 8 + 1 + 8 + 2 + 8 * 2 + 1 + 8 * 3;
+
+    pub fn matches(&self) -> [u64; 6] {
+        [self.match1, self.match2, self.match3, self.match4, self.match5, self.match6]
+    }
+
+    /// Prize pool of one match level, before it is split between its winners.
+    pub fn tier_reward(&self, tier: usize) -> u64 {
+        (self.total_reward as u128 * self.tiers[tier] as u128 / MAX_BASIS_POINTS as u128) as u64
+    }
+
+    /// Pari-mutuel rounds pay prizes in lamports from the prize pot instead of the mint vault.
+    pub fn is_pot_mode(&self) -> bool {
+        self.pot_bp > 0
+    }
+
+    /// Tier rewards left without winners that the rollover mode carries forward.
+    pub fn unallocated_rollover(&self) -> u64 {
+        let matches = self.matches();
+        let tiers = match self.rollover_mode {
+            RolloverMode::Disabled => 0..0,
+            RolloverMode::AllTiers => 0..matches.len(),
+            RolloverMode::TopTier => matches.len() - 1..matches.len(),
+        };
+        tiers
+            .filter(|&tier| matches[tier] == 0)
+            .map(|tier| self.tier_reward(tier))
+            .sum()
+    }
+
+    /// Pot lamports of a settled round that no tier pays out and no rollover carries forward.
+    pub fn pot_surplus(&self) -> u64 {
+        if !self.is_pot_mode() || !self.revealed || self.settled_shots != self.total_shots {
+            return 0;
+        }
+        self.total_reward
+            .saturating_sub(self.allocated)
+            .saturating_sub(self.unallocated_rollover())
+    }
+
+    /// Sets aside the tiers that were won once every ticket is counted, and opens the claim window.
+    pub fn finish_settlement(&mut self, now: u64) -> Result<(), ProgramError> {
+        self.allocated = (0..self.tiers.len())
+            .filter(|&tier| self.matches()[tier] > 0)
+            .map(|tier| self.tier_reward(tier))
+            .sum();
+        // winners only know their share from here, so the claim window starts now
+        if self.claim_period != 0 {
+            self.claim_deadline = now
+                .checked_add(self.claim_period)
+                .ok_or(AppError::CheckedCalculateFailed)?;
+        }
+        Ok(())
+    }
+
+    /// Reward tokens the mint vault must keep for this round, the whole pool until it is settled.
+    pub fn outstanding_reward(&self) -> u64 {
+        if self.is_pot_mode() {
+            return 0;
+        }
+        // nobody can win a round closed without tickets
+        let settled = (self.revealed && self.settled_shots == self.total_shots) || (self.closed && self.total_shots == 0);
+        if !settled {
+            return self.total_reward;
+        }
+        let unclaimed = if self.swept { 0 } else { self.allocated.saturating_sub(self.claimed) };
+        // tiers nobody won stay in the vault until a close carries them into a later round
+        let rollover = if self.rolled_over { 0 } else { self.unallocated_rollover() };
+        unclaimed + rollover
+    }
+
+    pub fn referral_share(&self, payment: u64) -> u64 {
+        (payment as u128 * self.referral_bp as u128 / MAX_BASIS_POINTS as u128) as u64
+    }
+
+    pub fn pot_share(&self, payment: u64) -> u64 {
+        (payment as u128 * self.pot_bp as u128 / MAX_BASIS_POINTS as u128) as u64
+    }
+
+    /// Recipients Buy pays before the remainder, in account order.
+    pub fn active_revenue_shares(&self) -> impl Iterator<Item = &RevenueShare> {
+        self.revenue_shares.iter().filter(|share| share.bp > 0)
+    }
+
+    pub fn new() -> ConfigureData {
+        ConfigureData {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
 
     pub fn from_account_info(a: &AccountInfo) -> Result<ConfigureData, ProgramError> {
         if a.data_len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
-        try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)
+        let data: ConfigureData = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
     }
 }
 
@@ -54,7 +485,8 @@ impl ConfigureData {
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct UserData {
-    pub shots: HashMap<[u8;6], u64>,
+    pub discriminator: [u8; 8],
+    pub version: u8,
     pub total_shots: u64,
     pub round: u64,
     pub reward: u64,
@@ -65,23 +497,131 @@ pub struct UserData {
     pub match4: u64,
     pub match5: u64,
     pub match6: u64,
+    /// ticket pages allocated by Buy
+    pub pages: u64,
+    /// ticket pages checked against the target by Settle
+    pub settled_pages: u64,
+    pub referrer: Option<Pubkey>,
 }
 
 impl UserData {
-    pub const LEN: usize = 200 + 1 + 8 * 9;
+    pub const DISCRIMINATOR: [u8; 8] = *b"userdata";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 8 * 9 + 8 * 2 + 33;
+
+    pub fn matches(&self) -> [u64; 6] {
+        [self.match1, self.match2, self.match3, self.match4, self.match5, self.match6]
+    }
+
+    pub fn is_winner(&self) -> bool {
+        self.matches().iter().any(|&matched| matched > 0)
+    }
+
+    pub fn new() -> UserData {
+        UserData {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
 
     pub fn from_account_info(a: &AccountInfo) -> Result<UserData, ProgramError> {
         if a.data_len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
-        try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)
+        let data: UserData = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TicketEntry {
+    pub shot: [u8; 6],
+    pub num: u64,
+}
+
+impl TicketEntry {
+    pub const LEN: usize = 6 + 8;
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct TicketPage {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub round: u64,
+    pub page: u64,
+    pub settled: bool,
+    pub tickets: Vec<TicketEntry>,
+}
+
+impl TicketPage {
+    pub const DISCRIMINATOR: [u8; 8] = *b"tktpage\0";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 8 * 2 + 1 + 4 + TicketEntry::LEN * TICKETS_PER_PAGE;
+
+    pub fn new() -> TicketPage {
+        TicketPage {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<TicketPage, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data: TicketPage = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
     }
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct BuyTicketsArgs {
-    pub num: u64
+    /// digits picked by the player, quick-pick when None
+    pub shot: Option<[u8; 6]>,
+    pub num: u64,
+    /// wallet credited with referral_bp of the payment, fixed by the first purchase of the round
+    pub referrer: Option<Pubkey>,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct CloseRoundArgs {
+    /// commitment of the round opened by this close, only taken from the authority
+    pub commitment: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct RevealTargetArgs {
+    pub secret: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SweepDestination {
+    /// transfer to a treasury token account
+    Treasury,
+    /// add to the total_reward of the round currently open, the config of RoundData.round
+    NextRound,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct SweepUnclaimedArgs {
+    pub destination: SweepDestination,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ProposeAuthorityArgs {
+    pub new_authority: Pubkey,
 }
 
 #[repr(C)]
@@ -96,20 +636,183 @@ pub struct ClearArgs {
     pub amt: u64
 }
 
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct WithdrawRevenueArgs {
+    pub amount: u64,
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct RoundData {
+    pub discriminator: [u8; 8],
+    pub version: u8,
     pub round: u64,
 }
 
 impl RoundData {
-    pub const LEN: usize = 8;
+    pub const DISCRIMINATOR: [u8; 8] = *b"round\0\0\0";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 8;
+
+    pub fn new() -> RoundData {
+        RoundData {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
 
     pub fn from_account_info(a: &AccountInfo) -> Result<RoundData, ProgramError> {
         if a.data_len() != Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
-        try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)
+        let data: RoundData = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
+    }
+}
+
+/// Reward tokens owed by every round paying out one mint, the share of the shared vault Clear leaves alone.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct MintLedger {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub mint: Pubkey,
+    /// sum of ConfigureData::outstanding_reward over the rounds of this mint
+    pub outstanding: u64,
+}
+
+impl MintLedger {
+    pub const DISCRIMINATOR: [u8; 8] = *b"mintldgr";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 8;
+
+    pub fn new() -> MintLedger {
+        MintLedger {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces `before`, what the round owed when the instruction started, with what it owes now.
+    pub fn track(&mut self, before: u64, config_data: &ConfigureData) -> Result<(), ProgramError> {
+        self.outstanding = self
+            .outstanding
+            .checked_add(config_data.outstanding_reward())
+            .and_then(|outstanding| outstanding.checked_sub(before))
+            .ok_or(AppError::CheckedCalculateFailed)?;
+        Ok(())
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<MintLedger, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data: MintLedger = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
+    }
+}
+
+/// Lifetime referral totals of one referrer, across all rounds.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ReferrerStats {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub referrer: Pubkey,
+    /// players who named this referrer on their first purchase of a round
+    pub referrals: u64,
+    pub tickets: u64,
+    pub lamports_earned: u64,
+    /// in base units of whichever payment mint the rounds used
+    pub tokens_earned: u64,
+}
+
+impl ReferrerStats {
+    pub const DISCRIMINATOR: [u8; 8] = *b"referrer";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 8 * 4;
+
+    pub fn new() -> ReferrerStats {
+        ReferrerStats {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<ReferrerStats, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data: ReferrerStats = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
     }
 }
 
+/// Program wide switches, held at the program_state PDA.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProgramState {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// key allowed to pause and unpause, independent of round authorities
+    pub guardian: Pubkey,
+    /// honored by BuyTickets only
+    pub paused_buys: bool,
+    /// honored by Claim and SweepUnclaimed, and by Clear, WithdrawRevenue and WithdrawPotSurplus too: claims are paused
+    /// when the vault balances or the ledger can't be trusted, and then an authority withdrawal is as suspect as a claim.
+    /// Nothing else takes from a vault: Settle, RevealTarget, Draw and ConsumeRandomness only tally and draw,
+    /// CloseRound's crank reward comes out of lamports the authority put on the config,
+    /// and CloseUserAccount and CloseRoundAccount only give back account rent
+    pub paused_claims: bool,
+}
+
+impl ProgramState {
+    pub const DISCRIMINATOR: [u8; 8] = *b"programs";
+    pub const VERSION: u8 = 1;
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + 32 + 1 + 1;
+
+    pub fn new() -> ProgramState {
+        ProgramState {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_account_info(a: &AccountInfo) -> Result<ProgramState, ProgramError> {
+        if a.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data: ProgramState = try_from_slice_unchecked(&a.data.borrow_mut()).map_err(|_| ProgramError::InvalidAccountData)?;
+        check_header(&data.discriminator, data.version, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(data)
+    }
+
+    /// Nothing is paused until the program state has been initialized.
+    pub fn load_or_default(a: &AccountInfo) -> Result<ProgramState, ProgramError> {
+        if a.data_is_empty() {
+            return Ok(ProgramState::default());
+        }
+        Self::from_account_info(a)
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct GuardianArgs {
+    pub guardian: Pubkey,
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct SetPauseArgs {
+    pub paused_buys: bool,
+    pub paused_claims: bool,
+}
@@ -1,7 +1,10 @@
+use arrayref::array_ref;
 use borsh::BorshDeserialize;
 use solana_program::{
     account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::ProgramResult,
+    keccak::Hash,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
@@ -12,12 +15,20 @@ use solana_program::{
 };
 use std::io::Error;
 
-use crate::error::AppError;
+use crate::{
+    error::AppError,
+    pda::*,
+    state::{ProgramState, MAX_BASIS_POINTS},
+};
 
 pub fn now_timestamp() -> u64 {
     Clock::get().unwrap().unix_timestamp as u64
 }
 
+pub fn now_slot() -> u64 {
+    Clock::get().unwrap().slot
+}
+
 pub fn assert_eq_pubkey(account_info: &AccountInfo, account: &Pubkey) -> ProgramResult {
     if account_info.key != account {
         Err(AppError::InvalidEqPubkey.into())
@@ -63,36 +74,45 @@ pub fn assert_derivation(
     account: &AccountInfo,
     path: &[&[u8]],
 ) -> Result<u8, ProgramError> {
-    let (key, bump) = Pubkey::find_program_address(&path, program_id);
+    let (key, bump) = Pubkey::find_program_address(path, program_id);
+    if key != *account.key {
+        return Err(AppError::InvalidDerivedKey.into());
+    }
+    Ok(bump)
+}
+
+/// Checks the account is the derived address, returning its bump.
+pub fn assert_address(account: &AccountInfo, (key, bump): (Pubkey, u8)) -> Result<u8, ProgramError> {
     if key != *account.key {
         return Err(AppError::InvalidDerivedKey.into());
     }
     Ok(bump)
 }
 
-pub fn assert_config(program_id: &Pubkey, account: &AccountInfo, round: String) -> Result<u8, ProgramError> {
-    let path = &[program_id.as_ref(), "config".as_bytes(), round.as_bytes()];
-    assert_derivation(&program_id, &account, path)
+pub fn assert_config(program_id: &Pubkey, account: &AccountInfo, round: u64) -> Result<u8, ProgramError> {
+    assert_address(account, find_config_address(program_id, round))
 }
 
 pub fn assert_round(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
-    let path = &[program_id.as_ref(), "round".as_bytes()];
-    assert_derivation(&program_id, &account, path)
+    assert_address(account, find_round_address(program_id))
 }
 
 pub fn assert_token_info(program_id: &Pubkey,new_mint: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
     let path = &[program_id.as_ref(), new_mint.as_ref(), "token_info".as_bytes()];
-    assert_derivation(&program_id, &account, path)
+    assert_derivation(program_id, account, path)
 }
 
 pub fn assert_token_vault(program_id: &Pubkey,new_mint: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
     let path = &[program_id.as_ref(), new_mint.as_ref(), "token_vault".as_bytes()];
-    assert_derivation(&program_id, &account, path)
+    assert_derivation(program_id, account, path)
 }
 
-pub fn assert_user_info(program_id: &Pubkey,user: &Pubkey, account: &AccountInfo, round: String) -> Result<u8, ProgramError> {
-    let path = &[program_id.as_ref(), user.as_ref(), "user_info".as_bytes(), round.as_bytes()];
-    assert_derivation(&program_id, &account, path)
+pub fn assert_user_info(program_id: &Pubkey, user: &Pubkey, account: &AccountInfo, round: u64) -> Result<u8, ProgramError> {
+    assert_address(account, find_user_info_address(program_id, user, round))
+}
+
+pub fn assert_ticket_page(program_id: &Pubkey, user: &Pubkey, account: &AccountInfo, round: u64, page: u64) -> Result<u8, ProgramError> {
+    assert_address(account, find_ticket_page_address(program_id, user, round, page))
 }
 
 pub fn assert_signer(account_info: &AccountInfo) -> ProgramResult {
@@ -103,6 +123,15 @@ pub fn assert_signer(account_info: &AccountInfo) -> ProgramResult {
     }
 }
 
+/// Tier basis points must pay something, never pay a lower match level more and fit in one pool.
+pub fn assert_tiers(tiers: &[u16; 6]) -> ProgramResult {
+    let total = tiers.iter().map(|&bp| bp as u64).sum::<u64>();
+    if total == 0 || total > MAX_BASIS_POINTS || tiers.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err(AppError::InvalidTiers.into());
+    }
+    Ok(())
+}
+
 pub fn assert_pda_creator(
     program_id: &Pubkey,
     collection_mint: &AccountInfo,
@@ -113,7 +142,7 @@ pub fn assert_pda_creator(
         collection_mint.key.as_ref(),
         "pda_creator".as_bytes(),
     ];
-    assert_derivation(&program_id, &pda_creator_info, path)
+    assert_derivation(program_id, pda_creator_info, path)
 }
 
 pub fn assert_collection(
@@ -126,7 +155,7 @@ pub fn assert_collection(
         collection_mint.key.as_ref(),
         "collection".as_bytes(),
     ];
-    assert_derivation(&program_id, &collection_info, path)
+    assert_derivation(program_id, collection_info, path)
 }
 
 pub fn assert_mint_vault(
@@ -134,12 +163,11 @@ pub fn assert_mint_vault(
     token: &AccountInfo,
     token_vault: &AccountInfo,
 ) -> Result<u8, ProgramError> {
-    let path = &[
-        program_id.as_ref(),
-        token.key.as_ref(),
-        "mint_vault".as_bytes(),
-    ];
-    assert_derivation(&program_id, &token_vault, path)
+    assert_address(token_vault, find_mint_vault_address(program_id, token.key))
+}
+
+pub fn assert_mint_ledger(program_id: &Pubkey, mint: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    assert_address(account, find_mint_ledger_address(program_id, mint))
 }
 
 pub fn assert_tranfer_authority(
@@ -147,12 +175,77 @@ pub fn assert_tranfer_authority(
     token: &AccountInfo,
     authority_info: &AccountInfo,
 ) -> Result<u8, ProgramError> {
-    let path = &[
-        program_id.as_ref(),
-        token.key.as_ref(),
-        "transfer_auth".as_bytes(),
-    ];
-    assert_derivation(&program_id, &authority_info, path)
+    assert_address(authority_info, find_transfer_auth_address(program_id, token.key))
+}
+
+pub fn assert_revenue_vault(
+    program_id: &Pubkey,
+    payment_mint: &Pubkey,
+    revenue_vault: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    assert_address(revenue_vault, find_revenue_vault_address(program_id, payment_mint))
+}
+
+pub fn assert_program_state(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    assert_address(account, find_program_state_address(program_id))
+}
+
+/// Checks the guardian has not paused claims, which stops every payout out of the vaults, the authority's included.
+pub fn assert_claims_not_paused(program_id: &Pubkey, program_state_info: &AccountInfo) -> ProgramResult {
+    assert_program_state(program_id, program_state_info)?;
+    if ProgramState::load_or_default(program_state_info)?.paused_claims {
+        return Err(AppError::ClaimsPaused.into());
+    }
+    Ok(())
+}
+
+/// Checks `authority` is the upgrade authority recorded in the program's ProgramData account.
+pub fn assert_upgrade_authority(
+    program_id: &Pubkey,
+    program_data_info: &AccountInfo,
+    authority: &Pubkey,
+) -> ProgramResult {
+    assert_address(program_data_info, find_program_data_address(program_id))?;
+    assert_owned_by(program_data_info, &bpf_loader_upgradeable::id())?;
+    // bincode layout: u32 variant (3 for ProgramData), u64 slot, Option<Pubkey> upgrade authority
+    let data = program_data_info.data.borrow();
+    if data.len() < UpgradeableLoaderState::size_of_programdata_metadata()
+        || data[0..4] != 3u32.to_le_bytes()
+        || data[12] != 1
+        || data[13..45] != authority.to_bytes()
+    {
+        return Err(AppError::InvalidAuthority.into());
+    }
+    Ok(())
+}
+
+pub fn assert_referrer_stats(program_id: &Pubkey, referrer: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    assert_address(account, find_referrer_stats_address(program_id, referrer))
+}
+
+pub fn assert_prize_pot(program_id: &Pubkey, account: &AccountInfo) -> Result<u8, ProgramError> {
+    assert_address(account, find_prize_pot_address(program_id))
+}
+
+/// Moves lamports out of a program owned account, keeping it rent exempt.
+pub fn withdraw_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let min = Rent::get()?.minimum_balance(from.data_len());
+    if from.lamports().saturating_sub(min) < amount {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Refunds every lamport of a program owned account and hands it back to the system program.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? += lamports;
+    account.realloc(0, false)?;
+    account.assign(&solana_program::system_program::id());
+    Ok(())
 }
 
 pub struct TokenTransferParams<'a: 'b, 'b> {
@@ -189,7 +282,7 @@ pub fn spl_token_transfer<'a>(
             amount,
         )?,
         &[source, destination, authority, token_program],
-        &[&signer_seeds],
+        &[signer_seeds],
     )
 }
 
@@ -212,7 +305,7 @@ pub fn create_or_allocate_account_raw<'a>(
     if required_lamports > 0 {
         msg!("Transfer {} lamports to the new account", required_lamports);
         invoke(
-            &system_instruction::transfer(&payer_info.key, new_account_info.key, required_lamports),
+            &system_instruction::transfer(payer_info.key, new_account_info.key, required_lamports),
             &[
                 payer_info.clone(),
                 new_account_info.clone(),
@@ -225,14 +318,14 @@ pub fn create_or_allocate_account_raw<'a>(
     invoke_signed(
         &system_instruction::allocate(new_account_info.key, size.try_into().unwrap()),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
 
     msg!("Assign the account to the owning program");
     invoke_signed(
         &system_instruction::assign(new_account_info.key, &program_id),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
     msg!("Completed assignation!");
 
@@ -240,6 +333,7 @@ pub fn create_or_allocate_account_raw<'a>(
 }
 
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 pub fn spl_token_create_account<'a>(
     token_program: &AccountInfo<'a>,
     payer_info: &AccountInfo<'a>,
@@ -251,7 +345,7 @@ pub fn spl_token_create_account<'a>(
     rent_info: &AccountInfo<'a>,
 ) -> ProgramResult {
     let size = spl_token::state::Account::LEN;
-    let rent = &Rent::from_account_info(&rent_info)?;
+    let rent = &Rent::from_account_info(rent_info)?;
     let required_lamports = rent.minimum_balance(size);
 
     msg!("spl_token_create_account create");
@@ -316,6 +410,7 @@ pub fn spl_token_transfer_invoke<'a>(
 }
 
 #[inline(always)]
+#[allow(clippy::too_many_arguments)]
 pub fn spl_token_create_mint<'a>(
     token_program: &AccountInfo<'a>,
     payer_info: &AccountInfo<'a>,
@@ -327,7 +422,7 @@ pub fn spl_token_create_mint<'a>(
     decimals: u8,
 ) -> Result<(), ProgramError> {
     let size = spl_token::state::Account::LEN;
-    let rent = &Rent::from_account_info(&rent_info)?;
+    let rent = &Rent::from_account_info(rent_info)?;
     let required_lamports = rent.minimum_balance(size);
 
     msg!("spl_token_create_token create");
@@ -433,4 +528,108 @@ pub fn spl_token_burn<'a>(
 #[inline(always)]
 pub fn count_matching_elements_until_difference(arr1: &[u8; 6], arr2: &[u8; 6]) -> usize {
     arr1.iter().zip(arr2.iter()).take_while(|(&a, &b)| a == b).count()
-}
\ No newline at end of file
+}
+
+#[inline(always)]
+pub fn hash_to_number(hash: &Hash) -> u64 {
+    let kep_bytes = hash.to_bytes();
+    let bts = array_ref![kep_bytes, 0, 16];
+    let num = u128::from_be_bytes(*bts);
+    (num % 1000000) as u64
+}
+
+#[inline(always)]
+pub fn number_to_digits(num: u64) -> [u8; 6] {
+    [
+        ((num / 100000) % 10) as u8,
+        ((num / 10000) % 10) as u8,
+        ((num / 1000) % 10) as u8,
+        ((num / 100) % 10) as u8,
+        ((num / 10) % 10) as u8,
+        (num % 10) as u8,
+    ]
+}
+
+/// Looks `slot` up in the raw SlotHashes sysvar, which is too large to deserialize in-program.
+pub fn find_slot_hash(slot_hashes_info: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>, ProgramError> {
+    const ENTRY_LEN: usize = 8 + 32;
+    let data = slot_hashes_info.try_borrow_data()?;
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let len = u64::from_le_bytes(*array_ref![data, 0, 8]) as usize;
+    if data.len() < 8 + len * ENTRY_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // entries are sorted by slot, newest first
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        let offset = 8 + mid * ENTRY_LEN;
+        let entry_slot = u64::from_le_bytes(*array_ref![data, offset, 8]);
+        if entry_slot == slot {
+            return Ok(Some(*array_ref![data, offset + 8, 32]));
+        } else if entry_slot > slot {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::sysvar;
+
+    /// Raw SlotHashes data, a length prefix then (slot, hash) entries newest first.
+    fn slot_hashes_data(slots: &[u64]) -> Vec<u8> {
+        let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+        for &slot in slots {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[slot as u8; 32]);
+        }
+        data
+    }
+
+    fn lookup(slots: &[u64], slot: u64) -> Option<[u8; 32]> {
+        let key = sysvar::slot_hashes::id();
+        let owner = sysvar::id();
+        let mut lamports = 0;
+        let mut data = slot_hashes_data(slots);
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        find_slot_hash(&info, slot).unwrap()
+    }
+
+    #[test]
+    fn test_find_slot_hash_hit() {
+        let slots = [120, 118, 117, 115, 110];
+        for slot in slots {
+            assert_eq!(lookup(&slots, slot), Some([slot as u8; 32]));
+        }
+    }
+
+    #[test]
+    fn test_find_slot_hash_skipped_slot() {
+        assert_eq!(lookup(&[120, 118, 117], 119), None);
+        assert_eq!(lookup(&[120, 118, 117], 121), None);
+        assert_eq!(lookup(&[], 120), None);
+    }
+
+    #[test]
+    fn test_find_slot_hash_aged_out() {
+        assert_eq!(lookup(&[520, 519, 518], 100), None);
+    }
+
+    #[test]
+    fn test_find_slot_hash_rejects_truncated_data() {
+        let key = sysvar::slot_hashes::id();
+        let owner = sysvar::id();
+        let mut lamports = 0;
+        let mut data = slot_hashes_data(&[120, 118]);
+        data.truncate(8 + 40);
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert_eq!(find_slot_hash(&info, 120), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use common::*;
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    account::AccountSharedData,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use token_factory::{builder::*, error::AppError, instruction::*, pda::*, state::*};
+
+#[tokio::test]
+async fn test_guardian_pauses_buys() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    start_oracle_round(&mut context, &payer, 0).await;
+
+    let guardian = Keypair::new();
+    let initialize = |signer: &Pubkey| {
+        initialize_program_state(
+            &program_id,
+            signer,
+            GuardianArgs {
+                guardian: guardian.pubkey(),
+            },
+        )
+        .unwrap()
+    };
+    // only the upgrade authority sets the first guardian, nobody can take the account before it
+    let stranger = Keypair::new();
+    fund(&mut context, &stranger.pubkey()).await;
+    let result = process(&mut context, &[initialize(&stranger.pubkey())], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+    process(&mut context, &[initialize(&payer)], &[]).await.unwrap();
+    let result = process(&mut context, &[initialize(&payer)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::AlreadyInitialized));
+
+    let pause = |paused_buys| {
+        set_pause(
+            &program_id,
+            &guardian.pubkey(),
+            SetPauseArgs {
+                paused_buys,
+                paused_claims: false,
+            },
+        )
+        .unwrap()
+    };
+    // only the guardian can pause, the round authority cannot
+    let mut by_authority = pause(true);
+    by_authority.accounts[0].pubkey = payer;
+    assert!(process(&mut context, &[by_authority], &[]).await.is_err());
+
+    process(&mut context, &[pause(true)], &[&guardian]).await.unwrap();
+    let result = process(&mut context, &[quick_pick(&payer, 0, false)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::BuysPaused));
+
+    process(&mut context, &[pause(false)], &[&guardian]).await.unwrap();
+    process(&mut context, &[quick_pick(&payer, 0, false)], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.total_shots, 4);
+}
+
+/// A program owned account holding `data` padded to `len`, as the first release allocated it.
+fn legacy_account(mut data: Vec<u8>, len: usize) -> AccountSharedData {
+    data.resize(len, 0);
+    let mut account = AccountSharedData::new(1_000_000, len, &token_factory::id());
+    account.set_data_from_slice(&data);
+    account
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_accounts() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let player = Pubkey::new_unique();
+    let (round_info, _) = find_round_address(&program_id);
+    let user_info = user_info_address(&player, 1);
+    // what the first release leaves after one close: round 1 closed, round 2 open and the counter on it
+    let open_config = LegacyConfigureData {
+        authority: payer,
+        charge_addr: payer,
+        token: Pubkey::new_unique(),
+        round: 1,
+        total_reward: TOTAL_REWARD,
+        target: 123456,
+        total_shots: 3,
+        match6: 1,
+        ..Default::default()
+    };
+    let legacy_config = LegacyConfigureData {
+        allocated: TOTAL_REWARD * 40 / 100,
+        closed: true,
+        ..open_config.clone()
+    };
+    // its close copied the open round into the next one, tallies included
+    let next_config = LegacyConfigureData {
+        round: 2,
+        target: 654321,
+        ..open_config
+    };
+    let legacy_user = LegacyUserData {
+        shots: [([1, 2, 3, 4, 5, 6], 1), ([0, 0, 0, 0, 0, 1], 2)].into_iter().collect(),
+        total_shots: 3,
+        round: 1,
+        reward: TOTAL_REWARD * 40 / 100,
+        claimed: true,
+        match6: 1,
+        ..Default::default()
+    };
+    let config = legacy_account(legacy_config.try_to_vec().unwrap(), LegacyConfigureData::LEN);
+    context.set_account(&config_address(1), &config);
+    let config = legacy_account(next_config.try_to_vec().unwrap(), LegacyConfigureData::LEN);
+    context.set_account(&config_address(2), &config);
+    let user = legacy_account(legacy_user.try_to_vec().unwrap(), LegacyUserData::LEN);
+    context.set_account(&user_info, &user);
+    context.set_account(&round_info, &legacy_account(2u64.to_le_bytes().to_vec(), 8));
+
+    let mint = legacy_config.token;
+    let migrate = |signer: &Pubkey, account: &Pubkey, round, user: Option<&Pubkey>, kind| {
+        let mint = (kind != AccountKind::Round).then_some(&mint);
+        let args = MigrateAccountArgs { kind };
+        migrate_account(&program_id, signer, account, &config_address(round), user, mint, args).unwrap()
+    };
+    // the kind has to match the address, and users and the round wait for their config
+    let result = process(&mut context, &[migrate(&payer, &config_address(1), 1, None, AccountKind::Round)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+    let result = process(&mut context, &[migrate(&payer, &user_info, 1, Some(&player), AccountKind::User)], &[]).await;
+    assert!(result.is_err());
+    let stranger = Keypair::new();
+    fund(&mut context, &stranger.pubkey()).await;
+    let by_stranger = migrate(&stranger.pubkey(), &config_address(1), 1, None, AccountKind::Config);
+    let result = process(&mut context, &[by_stranger], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    process(
+        &mut context,
+        &[
+            migrate(&payer, &config_address(1), 1, None, AccountKind::Config),
+            migrate(&payer, &config_address(2), 2, None, AccountKind::Config),
+            migrate(&payer, &user_info, 1, Some(&player), AccountKind::User),
+            migrate(&payer, &round_info, 2, None, AccountKind::Round),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let result = process(&mut context, &[migrate(&payer, &round_info, 2, None, AccountKind::Round)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::AlreadyMigrated));
+
+    let account = context.banks_client.get_account(config_address(1)).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ConfigureData::LEN);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.discriminator, ConfigureData::DISCRIMINATOR);
+    assert_eq!(config_data.authority, payer);
+    assert_eq!(config_data.token, mint);
+    assert_eq!(config_data.target, 123456);
+    assert_eq!(config_data.matches(), [0, 0, 0, 0, 0, 1]);
+    assert!(config_data.closed && config_data.revealed);
+    assert_eq!(config_data.settled_shots, 3);
+    assert_eq!(config_data.tiers, DEFAULT_TIERS);
+    assert_eq!(config_data.ticket_price, LegacyConfigureData::TICKET_PRICE);
+    assert_eq!(config_data.open_user_accounts, 1);
+
+    // the open round stays open, only tickets bought from here on wait for a target
+    let config_data: ConfigureData = get_state(&mut context, &config_address(2)).await;
+    assert!(!config_data.closed && !config_data.revealed);
+    assert_eq!(config_data.settled_shots, config_data.total_shots);
+    // the prize round 1 already paid is no longer owed, round 2 still owes its whole pool
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.claimed, legacy_user.reward);
+    let mint_ledger: MintLedger = get_state(&mut context, &find_mint_ledger_address(&program_id, &mint).0).await;
+    assert_eq!(mint_ledger.outstanding, TOTAL_REWARD);
+
+    let account = context.banks_client.get_account(user_info).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), UserData::LEN);
+    let user_data: UserData = get_state(&mut context, &user_info).await;
+    assert_eq!(user_data.discriminator, UserData::DISCRIMINATOR);
+    assert_eq!(user_data.total_shots, 3);
+    assert_eq!(user_data.matches(), [0, 0, 0, 0, 0, 1]);
+    assert!(user_data.claimed);
+    assert_eq!(user_data.reward, legacy_user.reward);
+    assert_eq!(user_data.pages, 0);
+
+    let round_data: RoundData = get_state(&mut context, &round_info).await;
+    assert_eq!(round_data.discriminator, RoundData::DISCRIMINATOR);
+    assert_eq!(round_data.round, 2);
+
+    // the migrated rounds carry on, the open one closes settled since its tickets were tallied when bought
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 2).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(2)).await;
+    assert!(config_data.closed && config_data.revealed);
+    assert_eq!(config_data.allocated, config_data.tier_reward(5));
+    let config_data: ConfigureData = get_state(&mut context, &config_address(3)).await;
+    assert!(!config_data.closed);
+    let round_data: RoundData = get_state(&mut context, &round_info).await;
+    assert_eq!(round_data.round, 3);
+}
+
+#[tokio::test]
+async fn test_authority_handover() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    start_round(&mut context, round_args(&payer, &payer)).await;
+    let config = config_address(1);
+    let successor = Keypair::new();
+    let stranger = Keypair::new();
+    let propose = |new_authority| {
+        propose_authority(&program_id, &payer, &config, ProposeAuthorityArgs { new_authority }).unwrap()
+    };
+    let accept = |signer: &Keypair| accept_authority(&program_id, &signer.pubkey(), &config).unwrap();
+
+    let result = process(&mut context, &[accept(&successor)], &[&successor]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidPendingAuthority));
+    let cancel = cancel_authority(&program_id, &payer, &config).unwrap();
+    let result = process(&mut context, &[cancel], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::NoPendingAuthority));
+
+    // a cancelled proposal can no longer be accepted
+    process(&mut context, &[propose(successor.pubkey())], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert_eq!(config_data.pending_authority, Some(successor.pubkey()));
+    let cancel = cancel_authority(&program_id, &payer, &config).unwrap();
+    process(&mut context, &[cancel], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert_eq!(config_data.pending_authority, None);
+    let result = process(&mut context, &[accept(&successor)], &[&successor]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidPendingAuthority));
+
+    process(&mut context, &[propose(successor.pubkey())], &[]).await.unwrap();
+    let result = process(&mut context, &[accept(&stranger)], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidPendingAuthority));
+    process(&mut context, &[accept(&successor)], &[&successor]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert_eq!(config_data.authority, successor.pubkey());
+    assert_eq!(config_data.pending_authority, None);
+
+    // the previous authority is locked out
+    let result = process(&mut context, &[propose(payer)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+}
+
+#[tokio::test]
+async fn test_guardian_pauses_payouts() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let buy = buy_instruction(&mut context, &payer, winning_shot(1, 1), 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    settle_user(&mut context, &payer, 1).await;
+
+    let guardian = Keypair::new();
+    let initialize = initialize_program_state(&program_id, &payer, GuardianArgs { guardian: guardian.pubkey() }).unwrap();
+    process(&mut context, &[initialize], &[]).await.unwrap();
+    let pause = |paused_claims| {
+        set_pause(
+            &program_id,
+            &guardian.pubkey(),
+            SetPauseArgs {
+                paused_buys: false,
+                paused_claims,
+            },
+        )
+        .unwrap()
+    };
+    process(&mut context, &[pause(true)], &[&guardian]).await.unwrap();
+
+    // neither the winner nor the authority moves tokens out while paused
+    let create_ata = create_associated_token_account(&payer, &payer, &mint, &spl_token::id());
+    process(&mut context, &[create_ata], &[]).await.unwrap();
+    let claim = ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let result = process(&mut context, std::slice::from_ref(&claim), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::ClaimsPaused));
+    let clear = ClearBuilder::new(&program_id, &payer, &mint, 2, 1).instruction().unwrap();
+    let result = process(&mut context, &[clear], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::ClaimsPaused));
+
+    process(&mut context, &[pause(false)], &[&guardian]).await.unwrap();
+    process(&mut context, &[claim], &[]).await.unwrap();
+    let user_data: UserData = get_state(&mut context, &user_info_address(&payer, 1)).await;
+    assert!(user_data.claimed);
+}
@@ -0,0 +1,283 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use token_factory::{builder::*, error::AppError, instruction::*, pda::*, state::*};
+
+const CLAIM_PERIOD: u64 = 100;
+const SURPLUS: u64 = 500;
+
+#[tokio::test]
+async fn test_clear_keeps_the_outstanding_reward() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let mint_vault = find_mint_vault_address(&program_id, &mint).0;
+    let payer_ata = get_associated_token_address(&payer, &mint);
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+            spl_token::instruction::mint_to(&spl_token::id(), &mint, &mint_vault, &payer, &[], SURPLUS).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let clear = |amount| ClearBuilder::new(&program_id, &payer, &mint, 1, amount).instruction().unwrap();
+
+    let other_mint = create_mint(&mut context).await;
+    let wrong_mint = ClearBuilder::new(&program_id, &payer, &other_mint, 1, 1)
+        .token_account(payer_ata)
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[wrong_mint], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidEqPubkey));
+    let mut wrong_vault = clear(1);
+    wrong_vault.accounts[3].pubkey = find_mint_vault_address(&program_id, &other_mint).0;
+    let result = process(&mut context, &[wrong_vault], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+
+    // the whole pool stays until the round is settled
+    let result = process(&mut context, &[clear(SURPLUS + 1)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+    process(&mut context, &[clear(SURPLUS)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut context, &mint_vault).await, TOTAL_REWARD);
+    assert_eq!(token_balance(&mut context, &payer_ata).await, SURPLUS);
+
+    // once settled only the unclaimed winnings stay, next to the pool of round 2 opened by the close
+    let buy = buy_instruction(&mut context, &payer, winning_shot(1, 1), 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    settle_user(&mut context, &payer, 1).await;
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    let next_config_data: ConfigureData = get_state(&mut context, &config_address(2)).await;
+    assert!(!next_config_data.closed);
+    assert_eq!(next_config_data.total_reward, TOTAL_REWARD);
+    let mint_ledger: MintLedger = get_state(&mut context, &find_mint_ledger_address(&program_id, &mint).0).await;
+    assert_eq!(mint_ledger.outstanding, config_data.allocated + TOTAL_REWARD);
+
+    // the settled round 1 does not open up the vault round 2 pays out of
+    let result = process(&mut context, &[clear(1)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+    let fund_next = spl_token::instruction::mint_to(&spl_token::id(), &mint, &mint_vault, &payer, &[], TOTAL_REWARD).unwrap();
+    process(&mut context, &[fund_next], &[]).await.unwrap();
+    let spare = TOTAL_REWARD - config_data.allocated;
+    let result = process(&mut context, &[clear(spare + 1)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+    process(&mut context, &[clear(spare)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut context, &mint_vault).await, config_data.allocated + TOTAL_REWARD);
+}
+
+#[tokio::test]
+async fn test_claim_deadline_starts_after_settlement() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        claim_period: CLAIM_PERIOD,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let mint_vault = find_mint_vault_address(&program_id, &mint).0;
+    let player = Keypair::new();
+    fund(&mut context, &player.pubkey()).await;
+    let shot = winning_shot(1, 2);
+    let buy = buy_instruction(&mut context, &payer, shot, 1).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+    let buy = buy_instruction(&mut context, &player.pubkey(), shot, 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[buy, close], &[&player]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+
+    // a slow settlement does not eat into the claim window
+    let settle_time = now(&mut context).await + 10 * CLAIM_PERIOD;
+    set_now(&mut context, settle_time).await;
+    settle_user(&mut context, &payer, 1).await;
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.claim_deadline, 0);
+    settle_user(&mut context, &player.pubkey(), 1).await;
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.claim_deadline, settle_time + CLAIM_PERIOD);
+
+    // a winner cannot take the reward out of another mint's vault
+    let other_mint = create_mint(&mut context).await;
+    let wrong_mint = ClaimBuilder::new(&program_id, &payer, &other_mint, 1).instruction().unwrap();
+    let result = process(&mut context, &[wrong_mint], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidEqPubkey));
+
+    let payer_ata = get_associated_token_address(&payer, &mint);
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+            ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let reward = config_data.tier_reward(5) / 2;
+    assert_eq!(token_balance(&mut context, &payer_ata).await, reward);
+
+    let treasury = Pubkey::new_unique();
+    let treasury_ata = get_associated_token_address(&treasury, &mint);
+    let create_treasury_ata = create_associated_token_account(&payer, &treasury, &mint, &spl_token::id());
+    process(&mut context, &[create_treasury_ata], &[]).await.unwrap();
+    let sweep = sweep_unclaimed(
+        &program_id,
+        &payer,
+        &config_address(1),
+        &treasury_ata,
+        &mint,
+        false,
+        SweepUnclaimedArgs {
+            destination: SweepDestination::Treasury,
+        },
+    )
+    .unwrap();
+    let result = process(&mut context, std::slice::from_ref(&sweep), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::ClaimPeriodNotOver));
+
+    set_now(&mut context, config_data.claim_deadline + 1).await;
+    let late_claim = ClaimBuilder::new(&program_id, &player.pubkey(), &mint, 1).instruction().unwrap();
+    let result = process(&mut context, &[late_claim], &[&player]).await;
+    assert_eq!(app_error(result), Some(AppError::ClaimExpired));
+
+    // only the player's unclaimed share is swept, the tiers nobody won stay in the vault
+    process(&mut context, std::slice::from_ref(&sweep), &[]).await.unwrap();
+    let unclaimed = config_data.allocated - reward;
+    assert_eq!(token_balance(&mut context, &treasury_ata).await, unclaimed);
+    assert_eq!(token_balance(&mut context, &mint_vault).await, TOTAL_REWARD - config_data.allocated);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.swept);
+    let result = process(&mut context, &[sweep], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::AlreadySwept));
+}
+
+#[tokio::test]
+async fn test_sweep_into_the_open_round() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        claim_period: CLAIM_PERIOD,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let buy = buy_instruction(&mut context, &payer, winning_shot(1, 1), 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    settle_user(&mut context, &payer, 1).await;
+
+    // round 2 closes during the claim window and round 3 takes over
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 2).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    set_now(&mut context, config_data.claim_deadline + 1).await;
+    let mint_ledger_address = find_mint_ledger_address(&program_id, &mint).0;
+    let mint_ledger: MintLedger = get_state(&mut context, &mint_ledger_address).await;
+
+    let sweep = |round| {
+        sweep_unclaimed(
+            &program_id,
+            &payer,
+            &config_address(1),
+            &config_address(round),
+            &mint,
+            false,
+            SweepUnclaimedArgs {
+                destination: SweepDestination::NextRound,
+            },
+        )
+        .unwrap()
+    };
+    let result = process(&mut context, &[sweep(2)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+    process(&mut context, &[sweep(3)], &[]).await.unwrap();
+
+    let next_config_data: ConfigureData = get_state(&mut context, &config_address(3)).await;
+    assert_eq!(next_config_data.total_reward, TOTAL_REWARD + config_data.allocated);
+    assert_eq!(next_config_data.carried_over, config_data.allocated);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.swept);
+    // the tokens never leave the vault, the debt only moves between rounds
+    let swept_ledger: MintLedger = get_state(&mut context, &mint_ledger_address).await;
+    assert_eq!(swept_ledger.outstanding, mint_ledger.outstanding);
+}
+
+#[tokio::test]
+async fn test_round_closes_once_every_winner_claimed() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let winners = [Keypair::new(), Keypair::new(), Keypair::new()];
+    for winner in winners.iter() {
+        fund(&mut context, &winner.pubkey()).await;
+        let buy = buy_instruction(&mut context, &winner.pubkey(), winning_shot(1, 3), 1).await;
+        process(&mut context, &[buy], &[winner]).await.unwrap();
+    }
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    for winner in winners.iter() {
+        settle_user(&mut context, &winner.pubkey(), 1).await;
+    }
+    let close_config = close_round_account(&program_id, &payer, &config_address(1)).unwrap();
+
+    for (claimed, winner) in winners.iter().enumerate() {
+        let result = process(&mut context, std::slice::from_ref(&close_config), &[]).await;
+        assert_eq!(app_error(result), Some(AppError::ClaimsPending));
+        let user = winner.pubkey();
+        let user_info = user_info_address(&user, 1);
+        let pages = [ticket_page_address(&user, 1, 0)];
+        // anyone may crank a user out of the round, but only once it has claimed, and only back to its own wallet
+        let close_user = close_user_account(&program_id, &payer, &config_address(1), &user, &user_info, &pages).unwrap();
+        let result = process(&mut context, std::slice::from_ref(&close_user), &[]).await;
+        assert_eq!(app_error(result), Some(AppError::NotClaimed));
+        process(
+            &mut context,
+            &[
+                create_associated_token_account(&user, &user, &mint, &spl_token::id()),
+                ClaimBuilder::new(&program_id, &user, &mint, 1).instruction().unwrap(),
+            ],
+            &[winner],
+        )
+        .await
+        .unwrap();
+        let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+        assert_eq!(config_data.winners, 3);
+        assert_eq!(config_data.claimed_winners, claimed as u64 + 1);
+        let mut wrong_wallet = close_user.clone();
+        wrong_wallet.accounts[2].pubkey = payer;
+        let result = process(&mut context, &[wrong_wallet], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+        let refund = balance(&mut context, &user_info).await + balance(&mut context, &pages[0]).await;
+        let before = balance(&mut context, &user).await;
+        process(&mut context, &[close_user], &[]).await.unwrap();
+        assert_eq!(balance(&mut context, &user).await, before + refund);
+    }
+
+    // the top tier splits three ways with a unit left over, which nobody can claim
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.allocated - config_data.claimed, config_data.tier_reward(5) % 3);
+    assert!(config_data.claimed < config_data.allocated);
+    process(&mut context, &[close_config], &[]).await.unwrap();
+    assert!(context.banks_client.get_account(config_address(1)).await.unwrap().is_none());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use token_factory::{builder::*, instruction::*, pda::*, state::*};
+
+#[cfg(feature = "client")]
+#[test]
+fn test_events_decoded_from_logs() {
+    use token_factory::event::*;
+
+    let program_id = token_factory::id();
+    let bought = AppEvent::TicketsBought(TicketsBought {
+        round: 1,
+        user: Pubkey::new_unique(),
+        num: 1,
+        amount: 50_000_000,
+        payment_mint: None,
+        referrer: None,
+        tickets: vec![TicketEntry { shot: [1, 2, 3, 4, 5, 6], num: 1 }],
+    });
+    let cleared = AppEvent::VaultCleared(VaultCleared {
+        authority: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        destination: Pubkey::new_unique(),
+        amount: 10,
+    });
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: Buy Tickets".to_string(),
+        format!("Program {} invoke [2]", mock_oracle::id()),
+        // data logged by another program must not be read as ours
+        cleared.to_log(),
+        format!("Program {} success", mock_oracle::id()),
+        bought.to_log(),
+        format!("Program {} success", program_id),
+        cleared.to_log(),
+    ];
+    assert_eq!(AppEvent::from_logs(&program_id, &logs), vec![bought]);
+}
+
+#[test]
+fn test_buy_builder_resolves_accounts() {
+    let program_id = token_factory::id();
+    let user = Pubkey::new_unique();
+    let referrer = Pubkey::new_unique();
+    let payment_mint = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let mut config_data = ConfigureData {
+        round: 3,
+        charge_addr: Pubkey::new_unique(),
+        payment_mint: Some(payment_mint),
+        ..ConfigureData::new()
+    };
+    config_data.revenue_shares[0] = RevenueShare {
+        recipient: treasury,
+        bp: 1000,
+    };
+    let args = BuyTicketsArgs {
+        num: 1,
+        referrer: Some(referrer),
+        ..Default::default()
+    };
+
+    // a user with two pages buys into the second one
+    let user_data = UserData {
+        pages: 2,
+        ..UserData::new()
+    };
+    let built = BuyBuilder::new(&program_id, &user, &config_data, Some(&user_data), args.clone())
+        .instruction()
+        .unwrap();
+    let expected = buy(
+        &program_id,
+        &user,
+        &config_address(3),
+        &user_info_address(&user, 3),
+        &config_data.charge_addr,
+        &ticket_page_address(&user, 3, 1),
+        &ticket_page_address(&user, 3, 2),
+        &[treasury],
+        false,
+        Some((
+            &get_associated_token_address(&referrer, &payment_mint),
+            &find_referrer_stats_address(&program_id, &referrer).0,
+        )),
+        Some((
+            &get_associated_token_address(&user, &payment_mint),
+            &find_revenue_vault_address(&program_id, &payment_mint).0,
+        )),
+        args.clone(),
+    )
+    .unwrap();
+    assert_eq!(built, expected);
+
+    // overrides replace the derived accounts
+    let token_account = Pubkey::new_unique();
+    let built = BuyBuilder::new(&program_id, &user, &config_data, None, args)
+        .token_account(token_account)
+        .instruction()
+        .unwrap();
+    assert!(built.accounts.iter().any(|meta| meta.pubkey == token_account));
+}
@@ -0,0 +1,356 @@
+//! Helpers shared by the program-test suites, each test binary uses a subset.
+#![allow(dead_code)]
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    hash::Hash,
+    keccak::hashv,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    slot_hashes::SlotHashes,
+    system_instruction,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use token_factory::{
+    builder::*,
+    error::AppError,
+    instruction::*,
+    pda::*,
+    state::*,
+    utils::{hash_to_number, number_to_digits},
+};
+
+pub const SECRET: [u8; 32] = [42; 32];
+/// hash reach_draw_slot records for the draw slot of a round
+pub const SLOT_HASH: [u8; 32] = [9; 32];
+pub const TOTAL_REWARD: u64 = 1_000_000;
+pub const TICKET_PRICE: u64 = 10_000_000;
+/// ticket price of the rounds opened by start_oracle_round
+pub const ORACLE_TICKET_PRICE: u64 = 50_000_000;
+
+/// token_factory next to the mock oracle its Oracle rounds read from.
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "token_factory",
+        token_factory::id(),
+        processor!(token_factory::processor::process_instruction),
+    );
+    program_test.add_program(
+        "mock_oracle",
+        mock_oracle::id(),
+        processor!(mock_oracle::processor::process_instruction),
+    );
+    program_test
+}
+
+/// A fresh bank whose payer is the upgrade authority, the only one opening rounds.
+pub async fn start() -> ProgramTestContext {
+    let mut context = program_test().start_with_context().await;
+    let payer = context.payer.pubkey();
+    set_upgrade_authority(&mut context, &payer).await;
+    context
+}
+
+/// Records `authority` as upgrade authority in a ProgramData account, as a deploy would.
+pub async fn set_upgrade_authority(context: &mut ProgramTestContext, authority: &Pubkey) {
+    let mut data = vec![0; UpgradeableLoaderState::size_of_programdata_metadata()];
+    data[0..4].copy_from_slice(&3u32.to_le_bytes());
+    data[12] = 1;
+    data[13..45].copy_from_slice(authority.as_ref());
+    let lamports = Rent::default().minimum_balance(data.len());
+    let mut account = AccountSharedData::new(lamports, data.len(), &bpf_loader_upgradeable::id());
+    account.set_data_from_slice(&data);
+    context.set_account(&find_program_data_address(&token_factory::id()).0, &account);
+    // paid by the payer, so the capitalization still adds up once the test warps
+    let payer = context.payer.pubkey();
+    let mut payer_account: AccountSharedData = context.banks_client.get_account(payer).await.unwrap().unwrap().into();
+    payer_account.set_lamports(payer_account.lamports() - lamports);
+    context.set_account(&payer, &payer_account);
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Decodes the AppError a failed transaction returned.
+pub fn app_error(result: Result<(), BanksClientError>) -> Option<AppError> {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, err) => AppError::from_instruction_error(&err),
+        _ => None,
+    }
+}
+
+pub async fn get_state<T: BorshDeserialize>(context: &mut ProgramTestContext, address: &Pubkey) -> T {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    token_factory::utils::try_from_slice_unchecked(&account.data).unwrap()
+}
+
+pub async fn balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub async fn now(context: &mut ProgramTestContext) -> u64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp as u64
+}
+
+pub async fn set_now(context: &mut ProgramTestContext, unix_timestamp: u64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp as i64;
+    context.set_sysvar(&clock);
+}
+
+pub fn config_address(round: u64) -> Pubkey {
+    find_config_address(&token_factory::id(), round).0
+}
+
+pub fn user_info_address(user: &Pubkey, round: u64) -> Pubkey {
+    find_user_info_address(&token_factory::id(), user, round).0
+}
+
+pub fn ticket_page_address(user: &Pubkey, round: u64, page: u64) -> Pubkey {
+    find_ticket_page_address(&token_factory::id(), user, round, page).0
+}
+
+/// The shot RevealTarget will draw once `total_shots` tickets are sold and reach_draw_slot has run.
+pub fn winning_shot(round: u64, total_shots: u64) -> [u8; 6] {
+    let hash = hashv(&[
+        &SECRET,
+        &SLOT_HASH,
+        config_address(round).as_ref(),
+        total_shots.to_be_bytes().as_slice(),
+    ]);
+    number_to_digits(hash_to_number(&hash))
+}
+
+/// Moves past the draw slot of a closed `round`, with SLOT_HASH recorded for it.
+pub async fn reach_draw_slot(context: &mut ProgramTestContext, round: u64) {
+    let config_data: ConfigureData = get_state(context, &config_address(round)).await;
+    context.warp_to_slot(config_data.draw_slot + 1).unwrap();
+    context.set_sysvar(&SlotHashes::new(&[(config_data.draw_slot, Hash::new_from_array(SLOT_HASH))]));
+}
+
+pub fn round_args(authority: &Pubkey, charge_addr: &Pubkey) -> ConfigureArgs {
+    ConfigureArgs {
+        authority: *authority,
+        charge_addr: *charge_addr,
+        round: "1".to_string(),
+        total_reward: TOTAL_REWARD,
+        commitment: hashv(&[&SECRET]).to_bytes(),
+        randomness_source: RandomnessSource::CommitReveal,
+        max_batch: 10,
+        tiers: DEFAULT_TIERS,
+        ticket_price: TICKET_PRICE,
+        ..Default::default()
+    }
+}
+
+/// Creates a mint with the payer as mint authority.
+pub async fn create_mint(context: &mut ProgramTestContext) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    process(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer, None, 6)
+                .unwrap(),
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+    mint.pubkey()
+}
+
+/// Creates the reward mint, opens round 1 with `args` and funds its vault.
+pub async fn start_round(context: &mut ProgramTestContext, args: ConfigureArgs) -> Pubkey {
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = create_mint(context).await;
+    process(
+        context,
+        &[
+            InitializeRoundBuilder::new(&program_id, &payer, &mint, args)
+                .instruction()
+                .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint,
+                &find_mint_vault_address(&program_id, &mint).0,
+                &payer,
+                &[],
+                TOTAL_REWARD,
+            )
+            .unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    mint
+}
+
+/// Opens an Oracle round 1 drawn by `oracle_authority` and buys two quick-picks in it.
+pub async fn start_oracle_round(context: &mut ProgramTestContext, oracle_authority: &Pubkey, pot_bp: u16) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        total_reward: if pot_bp > 0 { 0 } else { TOTAL_REWARD },
+        commitment: [0; 32],
+        randomness_source: RandomnessSource::Oracle,
+        oracle_program: mock_oracle::id(),
+        oracle_authority: *oracle_authority,
+        ticket_price: ORACLE_TICKET_PRICE,
+        pot_bp,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(context, args).await;
+    process(context, &[quick_pick(&payer, 0, pot_bp > 0)], &[]).await.unwrap();
+    mint
+}
+
+/// The buyer's entry in round 1, None before its first buy.
+pub async fn round_entry(context: &mut ProgramTestContext, buyer: &Pubkey) -> Option<UserData> {
+    let user_info = context.banks_client.get_account(user_info_address(buyer, 1)).await.unwrap();
+    user_info.map(|account| token_factory::utils::try_from_slice_unchecked(&account.data).unwrap())
+}
+
+/// Buys `shot` in round 1 through the builder, appended to the buyer's last page.
+pub async fn buy_instruction(context: &mut ProgramTestContext, buyer: &Pubkey, shot: [u8; 6], num: u64) -> Instruction {
+    let config_data: ConfigureData = get_state(context, &config_address(1)).await;
+    let user_data = round_entry(context, buyer).await;
+    BuyBuilder::new(
+        &token_factory::id(),
+        buyer,
+        &config_data,
+        user_data.as_ref(),
+        BuyTicketsArgs {
+            shot: Some(shot),
+            num,
+            referrer: None,
+        },
+    )
+    .instruction()
+    .unwrap()
+}
+
+/// `num` quick-picks in round 1 through the builder, appended to the buyer's last page.
+pub async fn buy_quick_picks(context: &mut ProgramTestContext, buyer: &Pubkey, num: u64) -> Instruction {
+    let config_data: ConfigureData = get_state(context, &config_address(1)).await;
+    let user_data = round_entry(context, buyer).await;
+    BuyBuilder::new(
+        &token_factory::id(),
+        buyer,
+        &config_data,
+        user_data.as_ref(),
+        BuyTicketsArgs {
+            num,
+            ..Default::default()
+        },
+    )
+    .instruction()
+    .unwrap()
+}
+
+/// Two quick-pick tickets in round 1, charged to the buyer itself.
+pub fn quick_pick(buyer: &Pubkey, page: u64, pot_mode: bool) -> Instruction {
+    buy(
+        &token_factory::id(),
+        buyer,
+        &config_address(1),
+        &user_info_address(buyer, 1),
+        buyer,
+        &ticket_page_address(buyer, 1, page),
+        &ticket_page_address(buyer, 1, page + 1),
+        &[],
+        pot_mode,
+        None,
+        None,
+        BuyTicketsArgs {
+            num: 2,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Requests oracle randomness for round 1 and closes it with the request bound.
+pub async fn close_with_oracle(context: &mut ProgramTestContext, oracle_authority: &Pubkey) -> Pubkey {
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let config_data: ConfigureData = get_state(context, &config_address(1)).await;
+    let mint = config_data.token;
+    let randomness = Keypair::new();
+    process(
+        context,
+        &[
+            mock_oracle::instruction::request(&mock_oracle::id(), &payer, &randomness.pubkey(), oracle_authority)
+                .unwrap(),
+            CloseBuilder::new(&program_id, &payer, &mint, 1)
+                .randomness(randomness.pubkey())
+                .instruction()
+                .unwrap(),
+        ],
+        &[&randomness],
+    )
+    .await
+    .unwrap();
+    randomness.pubkey()
+}
+
+/// Settles every ticket page of `user` in `round`.
+pub async fn settle_user(context: &mut ProgramTestContext, user: &Pubkey, round: u64) {
+    let config_data: ConfigureData = get_state(context, &config_address(round)).await;
+    let user_data: UserData = get_state(context, &user_info_address(user, round)).await;
+    for page in 0..user_data.pages {
+        let settle = settle(
+            &token_factory::id(),
+            &config_address(round),
+            &config_data.token,
+            user,
+            &user_info_address(user, round),
+            &ticket_page_address(user, round, page),
+        )
+        .unwrap();
+        process(context, &[settle], &[]).await.unwrap();
+    }
+}
+
+pub async fn fund(context: &mut ProgramTestContext, to: &Pubkey) {
+    let transfer = system_instruction::transfer(&context.payer.pubkey(), to, 1_000_000_000);
+    process(context, &[transfer], &[]).await.unwrap();
+}
@@ -0,0 +1,37 @@
+//! Compute budget checks, run against the SBF build with `cargo test-sbf --features test-bpf`.
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTest;
+use solana_sdk::{signature::Signer, transaction::Transaction};
+use token_factory::state::*;
+
+/// Default per-instruction compute budget.
+const MAX_UNITS: u64 = 200_000;
+
+#[tokio::test]
+async fn test_max_batch_buy_fits_compute_budget() {
+    let mut context = ProgramTest::new("token_factory", token_factory::id(), None)
+        .start_with_context()
+        .await;
+    let payer = context.payer.pubkey();
+    set_upgrade_authority(&mut context, &payer).await;
+    let args = ConfigureArgs {
+        max_batch: MAX_BATCH_SIZE,
+        ..round_args(&payer, &payer)
+    };
+    start_round(&mut context, args).await;
+
+    // one ticket already on the first page, so the largest batch also spills into a new page
+    let first = buy_quick_picks(&mut context, &payer, 1).await;
+    process(&mut context, &[first], &[]).await.unwrap();
+    let buy = buy_quick_picks(&mut context, &payer, MAX_BATCH_SIZE).await;
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[buy], Some(&payer), &[&context.payer], blockhash);
+    let simulation = context.banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    let units = simulation.simulation_details.unwrap().units_consumed;
+    assert!(units <= MAX_UNITS, "a {} ticket buy used {} units", MAX_BATCH_SIZE, units);
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use token_factory::{builder::*, error::AppError, instruction::*, state::*};
+
+/// Basis points over the whole pool, a lower match level paid more than a higher one, nothing paid.
+const INVALID_TIERS: [[u16; 6]; 3] = [
+    [200, 300, 500, 2000, 3000, 4001],
+    [200, 300, 2000, 500, 3000, 4000],
+    [0; 6],
+];
+
+const CLAIM_PERIOD: u64 = 100;
+
+#[tokio::test]
+async fn test_initialize_round_rejects_invalid_tiers() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = create_mint(&mut context).await;
+
+    for tiers in INVALID_TIERS {
+        let args = ConfigureArgs {
+            tiers,
+            ..round_args(&payer, &payer)
+        };
+        let initialize = InitializeRoundBuilder::new(&program_id, &payer, &mint, args).instruction().unwrap();
+        let result = process(&mut context, &[initialize], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::InvalidTiers), "{:?}", tiers);
+    }
+
+    let args = ConfigureArgs {
+        tiers: [0, 0, 0, 0, 0, MAX_BASIS_POINTS as u16],
+        ..round_args(&payer, &payer)
+    };
+    let initialize = InitializeRoundBuilder::new(&program_id, &payer, &mint, args).instruction().unwrap();
+    process(&mut context, &[initialize], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_update_config_rejects_invalid_tiers() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let update = |tiers| {
+        update_config(
+            &program_id,
+            &payer,
+            &config_address(1),
+            &mint,
+            UpdateConfigArgs {
+                tiers: Some(tiers),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    for tiers in INVALID_TIERS {
+        let result = process(&mut context, &[update(tiers)], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::InvalidTiers), "{:?}", tiers);
+    }
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.tiers, DEFAULT_TIERS);
+
+    let tiers = [100, 100, 500, 1000, 2000, 6000];
+    process(&mut context, &[update(tiers)], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.tiers, tiers);
+}
+
+#[tokio::test]
+async fn test_update_config_keeps_sold_terms() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let start_time = now(&mut context).await;
+    let end_time = start_time + 3600;
+    let args = ConfigureArgs {
+        start_time,
+        end_time,
+        claim_period: CLAIM_PERIOD,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let update = |args| update_config(&program_id, &payer, &config_address(1), &mint, args).unwrap();
+
+    // before the first sale every term can still move
+    let terms = UpdateConfigArgs {
+        charge_addr: Some(Pubkey::new_unique()),
+        total_reward: Some(TOTAL_REWARD / 2),
+        end_time: Some(end_time - 600),
+        claim_period: Some(1),
+        ..Default::default()
+    };
+    process(&mut context, &[update(terms)], &[]).await.unwrap();
+    let restore = UpdateConfigArgs {
+        charge_addr: Some(payer),
+        total_reward: Some(TOTAL_REWARD),
+        end_time: Some(end_time),
+        claim_period: Some(CLAIM_PERIOD),
+        ..Default::default()
+    };
+    process(&mut context, &[update(restore)], &[]).await.unwrap();
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 1).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+
+    let frozen = [
+        UpdateConfigArgs {
+            charge_addr: Some(Pubkey::new_unique()),
+            ..Default::default()
+        },
+        UpdateConfigArgs {
+            total_reward: Some(TOTAL_REWARD - 1),
+            ..Default::default()
+        },
+        UpdateConfigArgs {
+            end_time: Some(end_time - 1),
+            ..Default::default()
+        },
+        UpdateConfigArgs {
+            claim_period: Some(CLAIM_PERIOD - 1),
+            ..Default::default()
+        },
+    ];
+    for args in frozen {
+        let result = process(&mut context, &[update(args.clone())], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::TicketsSold), "{:?}", args);
+    }
+
+    let extended = UpdateConfigArgs {
+        total_reward: Some(TOTAL_REWARD + 1),
+        end_time: Some(end_time + 1),
+        claim_period: Some(CLAIM_PERIOD + 1),
+        ..Default::default()
+    };
+    process(&mut context, &[update(extended)], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.total_reward, TOTAL_REWARD + 1);
+    assert_eq!(config_data.end_time, end_time + 1);
+    assert_eq!(config_data.charge_addr, payer);
+    assert_eq!(config_data.claim_period, CLAIM_PERIOD + 1);
+
+    // dropping the end time keeps the sale open for good, which only extends it
+    let open_ended = UpdateConfigArgs {
+        end_time: Some(0),
+        claim_period: Some(0),
+        ..Default::default()
+    };
+    process(&mut context, &[update(open_ended)], &[]).await.unwrap();
+    let reinstated = [
+        UpdateConfigArgs {
+            end_time: Some(end_time + 2),
+            ..Default::default()
+        },
+        UpdateConfigArgs {
+            claim_period: Some(CLAIM_PERIOD + 2),
+            ..Default::default()
+        },
+    ];
+    for args in reinstated {
+        let result = process(&mut context, &[update(args.clone())], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::TicketsSold), "{:?}", args);
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::*;
+use solana_program::{clock::Clock, hash::Hash, keccak::hashv, slot_hashes::SlotHashes};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+use token_factory::{builder::*, error::AppError, instruction::*, state::*, utils::hash_to_number};
+
+async fn slot(context: &mut ProgramTestContext) -> u64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.slot
+}
+
+/// Moves past `slot` and replaces SlotHashes with `entries`, newest first.
+fn warp_with_slot_hashes(context: &mut ProgramTestContext, slot: u64, entries: &[(u64, Hash)]) {
+    context.warp_to_slot(slot).unwrap();
+    context.set_sysvar(&SlotHashes::new(entries));
+}
+
+#[tokio::test]
+async fn test_draw_from_slot_hashes() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        randomness_source: RandomnessSource::SlotHashes,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 3).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    let draw_slot = config_data.draw_slot;
+    assert_eq!(draw_slot, slot(&mut context).await + DRAW_SLOT_DELAY);
+    let result = process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::DrawSlotNotReached));
+
+    // the draw slot was skipped, anyone drawing now schedules a later one
+    let now_slot = draw_slot + 5;
+    let skipped = [(draw_slot + 1, Hash::new_unique()), (draw_slot - 1, Hash::new_unique())];
+    warp_with_slot_hashes(&mut context, now_slot, &skipped);
+    process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(!config_data.revealed);
+    assert_eq!(config_data.draw_slot, now_slot + DRAW_SLOT_DELAY);
+
+    let draw_slot = config_data.draw_slot;
+    let slot_hash = Hash::new_unique();
+    let recorded = [(draw_slot + 1, Hash::new_unique()), (draw_slot, slot_hash)];
+    warp_with_slot_hashes(&mut context, draw_slot + 2, &recorded);
+    process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await.unwrap();
+
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.revealed);
+    let hash = hashv(&[slot_hash.as_ref(), config_address(1).as_ref(), 3u64.to_be_bytes().as_slice()]);
+    assert_eq!(config_data.target, hash_to_number(&hash));
+
+    let result = process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::TargetRevealed));
+}
+
+#[tokio::test]
+async fn test_unrevealed_round_falls_back_to_slot_hashes() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 3).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    let draw_slot = config_data.draw_slot;
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+
+    // nobody takes the draw from the authority while it can still reveal
+    warp_with_slot_hashes(&mut context, draw_slot + 1, &[(draw_slot, Hash::new_unique())]);
+    let result = process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RevealWindowOpen));
+
+    // an aged out slot gives the authority no second target
+    let now_slot = draw_slot + 600;
+    warp_with_slot_hashes(&mut context, now_slot, &[(now_slot - 1, Hash::new_unique())]);
+    let result = process(&mut context, std::slice::from_ref(&reveal), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RevealWindowPassed));
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(!config_data.revealed);
+    assert_eq!(config_data.draw_slot, draw_slot);
+
+    // anyone moves the round to a later slot and draws it without the secret
+    process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.randomness_source, RandomnessSource::SlotHashes);
+    assert_eq!(config_data.draw_slot, now_slot + DRAW_SLOT_DELAY);
+    let result = process(&mut context, &[reveal], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidRandomnessSource));
+
+    let draw_slot = config_data.draw_slot;
+    let slot_hash = Hash::new_unique();
+    warp_with_slot_hashes(&mut context, draw_slot + 1, &[(draw_slot, slot_hash)]);
+    process(&mut context, &[draw(&program_id, &config_address(1)).unwrap()], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.revealed);
+    let hash = hashv(&[slot_hash.as_ref(), config_address(1).as_ref(), 3u64.to_be_bytes().as_slice()]);
+    assert_eq!(config_data.target, hash_to_number(&hash));
+}
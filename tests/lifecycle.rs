@@ -0,0 +1,485 @@
+mod common;
+
+use common::*;
+use solana_program::{program_pack::Pack, pubkey::Pubkey, system_instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use token_factory::{builder::*, error::AppError, instruction::*, pda::*, state::*, utils::number_to_digits};
+
+#[tokio::test]
+async fn test_round_lifecycle() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let charge_addr = Pubkey::new_unique();
+    let mint = start_round(&mut context, round_args(&payer, &charge_addr)).await;
+
+    let shot = winning_shot(1, 2);
+    let buy = buy_instruction(&mut context, &payer, shot, 2).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+    assert_eq!(context.banks_client.get_balance(charge_addr).await.unwrap(), 2 * TICKET_PRICE);
+    let user_info = find_user_info_address(&program_id, &payer, 1).0;
+    let user_data: UserData = get_state(&mut context, &user_info).await;
+    assert_eq!(user_data.total_shots, 2);
+
+    let create_ata = create_associated_token_account(&payer, &payer, &mint, &spl_token::id());
+    process(&mut context, &[create_ata], &[]).await.unwrap();
+    let claim = ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let result = process(&mut context, std::slice::from_ref(&claim), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::SaleNotClosed));
+
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    let result = process(&mut context, std::slice::from_ref(&claim), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::TargetNotRevealed));
+
+    let config = find_config_address(&program_id, 1).0;
+    let wrong_secret = reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: [0; 32] }).unwrap();
+    let result = process(&mut context, &[wrong_secret], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidSecret));
+    // the authority knows the secret, so the draw waits for a slot hash nobody had at close
+    let early = reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: SECRET }).unwrap();
+    let result = process(&mut context, &[early], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::DrawSlotNotReached));
+    reach_draw_slot(&mut context, 1).await;
+    process(
+        &mut context,
+        &[
+            reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: SECRET }).unwrap(),
+            settle(
+                &program_id,
+                &config,
+                &mint,
+                &payer,
+                &user_info,
+                &find_ticket_page_address(&program_id, &payer, 1, 0).0,
+            )
+            .unwrap(),
+            claim.clone(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // the only tickets hit all six digits, so they take the whole top tier
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert_eq!(number_to_digits(config_data.target), shot);
+    assert_eq!(config_data.match6, 2);
+    let user_data: UserData = get_state(&mut context, &user_info).await;
+    assert!(user_data.claimed);
+    assert_eq!(user_data.reward, config_data.tier_reward(5));
+    assert_eq!(config_data.claimed, user_data.reward);
+    let account = context
+        .banks_client
+        .get_account(get_associated_token_address(&payer, &mint))
+        .await
+        .unwrap()
+        .unwrap();
+    let token = spl_token::state::Account::unpack(&account.data).unwrap();
+    assert_eq!(token.amount, user_data.reward);
+
+    let result = process(&mut context, &[claim], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::AlreadyClaimed));
+
+    let next_config_data: ConfigureData = get_state(&mut context, &find_config_address(&program_id, 2).0).await;
+    assert_eq!(next_config_data.round, 2);
+    assert!(!next_config_data.closed);
+    let round_data: RoundData = get_state(&mut context, &find_round_address(&program_id).0).await;
+    assert_eq!(round_data.round, 2);
+}
+
+#[tokio::test]
+async fn test_start_time_gates_buys() {
+    let mut context = start().await;
+    let payer = context.payer.pubkey();
+    let start_time = now(&mut context).await + 3600;
+    let args = ConfigureArgs {
+        start_time,
+        end_time: start_time + 3600,
+        ..round_args(&payer, &payer)
+    };
+    start_round(&mut context, args).await;
+
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 1).await;
+    let result = process(&mut context, std::slice::from_ref(&buy), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::SaleNotOpen));
+
+    set_now(&mut context, start_time).await;
+    process(&mut context, std::slice::from_ref(&buy), &[]).await.unwrap();
+
+    set_now(&mut context, start_time + 3600).await;
+    let result = process(&mut context, &[buy], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::SaleEnded));
+}
+
+#[tokio::test]
+async fn test_rejects_wrong_authority() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let stranger = Keypair::new();
+    fund(&mut context, &stranger.pubkey()).await;
+    let config = find_config_address(&program_id, 1).0;
+
+    // before end_time only the authority can close
+    let close = CloseBuilder::new(&program_id, &stranger.pubkey(), &mint, 1).instruction().unwrap();
+    let result = process(&mut context, &[close], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    let update = update_config(
+        &program_id,
+        &stranger.pubkey(),
+        &config,
+        &mint,
+        UpdateConfigArgs {
+            charge_addr: Some(stranger.pubkey()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let result = process(&mut context, &[update], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    let stranger_ata = get_associated_token_address(&stranger.pubkey(), &mint);
+    process(
+        &mut context,
+        &[create_associated_token_account(&payer, &stranger.pubkey(), &mint, &spl_token::id())],
+        &[],
+    )
+    .await
+    .unwrap();
+    let clear = ClearBuilder::new(&program_id, &stranger.pubkey(), &mint, 1, TOTAL_REWARD)
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[clear], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidEqPubkey));
+
+    process(&mut context, &[CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap()], &[])
+        .await
+        .unwrap();
+    let reveal = reveal_target(&program_id, &stranger.pubkey(), &config, RevealTargetArgs { secret: SECRET })
+        .unwrap();
+    let result = process(&mut context, &[reveal], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidEqPubkey));
+
+    let account = context.banks_client.get_account(stranger_ata).await.unwrap().unwrap();
+    assert_eq!(spl_token::state::Account::unpack(&account.data).unwrap().amount, 0);
+}
+
+#[tokio::test]
+async fn test_rejects_wrong_pdas() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+
+    // a vault that is not derived from the mint
+    let mint = Pubkey::new_unique();
+    let mut initialize = InitializeRoundBuilder::new(&program_id, &payer, &mint, round_args(&payer, &payer))
+        .instruction()
+        .unwrap();
+    initialize.accounts[4].pubkey = Pubkey::new_unique();
+    let result = process(&mut context, &[initialize], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let config_data: ConfigureData = get_state(&mut context, &find_config_address(&program_id, 1).0).await;
+    let other = Pubkey::new_unique();
+    let buy = BuyBuilder::new(
+        &program_id,
+        &payer,
+        &config_data,
+        None,
+        BuyTicketsArgs {
+            shot: Some(winning_shot(1, 1)),
+            num: 1,
+            referrer: None,
+        },
+    );
+    let someone_elses_entry = buy
+        .clone()
+        .user_info(find_user_info_address(&program_id, &other, 1).0)
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[someone_elses_entry], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+    process(&mut context, &[buy.instruction().unwrap()], &[]).await.unwrap();
+
+    let config = find_config_address(&program_id, 1).0;
+    let user_info = find_user_info_address(&program_id, &payer, 1).0;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(
+        &mut context,
+        &[
+            reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: SECRET }).unwrap(),
+            settle(
+                &program_id,
+                &config,
+                &mint,
+                &payer,
+                &user_info,
+                &find_ticket_page_address(&program_id, &payer, 1, 0).0,
+            )
+            .unwrap(),
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // the config of the next round does not derive from round 1
+    let wrong_config = ClaimBuilder::new(&program_id, &payer, &mint, 1)
+        .config(find_config_address(&program_id, 2).0)
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[wrong_config], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+
+    let mut wrong_vault = ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    wrong_vault.accounts[4].pubkey = find_mint_vault_address(&program_id, &Pubkey::new_unique()).0;
+    let result = process(&mut context, &[wrong_vault], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidDerivedKey));
+
+    let claim = ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[claim], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reveal_target_checks_commitment() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 3).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    let config = find_config_address(&program_id, 1).0;
+
+    let mismatched = reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: [7; 32] }).unwrap();
+    let result = process(&mut context, &[mismatched], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidSecret));
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert!(!config_data.revealed);
+
+    let matching = reveal_target(&program_id, &payer, &config, RevealTargetArgs { secret: SECRET }).unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, std::slice::from_ref(&matching), &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config).await;
+    assert!(config_data.revealed);
+    assert_eq!(number_to_digits(config_data.target), winning_shot(1, 3));
+
+    let result = process(&mut context, &[matching], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::TargetRevealed));
+}
+
+#[tokio::test]
+async fn test_anyone_closes_after_end_time() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let crank_reward = 5_000_000;
+    let start_time = now(&mut context).await;
+    let args = ConfigureArgs {
+        start_time,
+        end_time: start_time + 3600,
+        crank_reward,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let cranker = Keypair::new();
+    fund(&mut context, &cranker.pubkey()).await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let config_rent = rent.minimum_balance(ConfigureData::LEN);
+
+    let close = |round| CloseBuilder::new(&program_id, &cranker.pubkey(), &mint, round).instruction().unwrap();
+    let result = process(&mut context, &[close(1)], &[&cranker]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    // a deposit short of the reward pays nothing, the config keeps its rent and the deposit
+    let short = system_instruction::transfer(&payer, &config_address(1), crank_reward - 1);
+    process(&mut context, &[short], &[]).await.unwrap();
+    set_now(&mut context, start_time + 3600).await;
+    let before = balance(&mut context, &cranker.pubkey()).await;
+    process(&mut context, &[close(1)], &[&cranker]).await.unwrap();
+    assert_eq!(balance(&mut context, &cranker.pubkey()).await, before - config_rent);
+    assert_eq!(balance(&mut context, &config_address(1)).await, config_rent + crank_reward - 1);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.closed);
+
+    // the next round inherits the sale length and the reward, paid above the rent floor only
+    let next_config_data: ConfigureData = get_state(&mut context, &config_address(2)).await;
+    assert_eq!(next_config_data.end_time, start_time + 2 * 3600);
+    assert_eq!(next_config_data.crank_reward, crank_reward);
+    let deposit = system_instruction::transfer(&payer, &config_address(2), crank_reward);
+    process(&mut context, &[deposit], &[]).await.unwrap();
+    set_now(&mut context, next_config_data.end_time).await;
+    let before = balance(&mut context, &cranker.pubkey()).await;
+    process(&mut context, &[close(2)], &[&cranker]).await.unwrap();
+    assert_eq!(balance(&mut context, &cranker.pubkey()).await, before - config_rent + crank_reward);
+    assert_eq!(balance(&mut context, &config_address(2)).await, config_rent);
+}
+
+#[tokio::test]
+async fn test_rollover_moves_into_the_round_it_is_handed_to_once() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        rollover_mode: RolloverMode::AllTiers,
+        ..round_args(&payer, &payer)
+    };
+    let mint = start_round(&mut context, args).await;
+    let buy = buy_instruction(&mut context, &payer, winning_shot(1, 1), 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    settle_user(&mut context, &payer, 1).await;
+
+    // only the top tier was won, the rest is carried by the close round 1 is handed to
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    let rollover = config_data.unallocated_rollover();
+    assert_eq!(rollover, TOTAL_REWARD - config_data.tier_reward(5));
+    let mint_ledger_address = find_mint_ledger_address(&program_id, &mint).0;
+    let mint_ledger: MintLedger = get_state(&mut context, &mint_ledger_address).await;
+    assert_eq!(mint_ledger.outstanding, config_data.allocated + rollover + TOTAL_REWARD);
+
+    // with round 2 funded the vault holds exactly what is owed, the pending rollover included
+    let mint_vault = find_mint_vault_address(&program_id, &mint).0;
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+            spl_token::instruction::mint_to(&spl_token::id(), &mint, &mint_vault, &payer, &[], TOTAL_REWARD).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let clear = ClearBuilder::new(&program_id, &payer, &mint, 1, 1).instruction().unwrap();
+    let result = process(&mut context, &[clear], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 2).rollover_round(1).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    // the rollover moves into round 3, and round 2, closed without tickets, keeps its own pool pending
+    let mint_ledger: MintLedger = get_state(&mut context, &mint_ledger_address).await;
+    assert_eq!(mint_ledger.outstanding, config_data.allocated + 2 * TOTAL_REWARD + rollover);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.rolled_over);
+    assert_eq!(config_data.rollover, rollover);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(3)).await;
+    assert_eq!(config_data.total_reward, TOTAL_REWARD + rollover);
+    assert_eq!(config_data.carried_over, rollover);
+
+    let again = CloseBuilder::new(&program_id, &payer, &mint, 3).rollover_round(1).instruction().unwrap();
+    let result = process(&mut context, &[again], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::AlreadyRolledOver));
+
+    // the carried amount is not copied on, round 4 starts from the base pool again
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 3).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(4)).await;
+    assert_eq!(config_data.total_reward, TOTAL_REWARD);
+    assert_eq!(config_data.carried_over, 0);
+}
+
+#[tokio::test]
+async fn test_round_account_outlives_user_accounts() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let buy = buy_instruction(&mut context, &payer, winning_shot(1, 1), 1).await;
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    let reveal = reveal_target(&program_id, &payer, &config_address(1), RevealTargetArgs { secret: SECRET }).unwrap();
+    process(&mut context, &[buy, close], &[]).await.unwrap();
+    reach_draw_slot(&mut context, 1).await;
+    process(&mut context, &[reveal], &[]).await.unwrap();
+    settle_user(&mut context, &payer, 1).await;
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+            ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let open_round = close_round_account(&program_id, &payer, &config_address(2)).unwrap();
+    let result = process(&mut context, &[open_round], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RoundNotSettled));
+
+    // the user info and its page still need the config to be closed
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.open_user_accounts, 2);
+    let close_config = close_round_account(&program_id, &payer, &config_address(1)).unwrap();
+    let result = process(&mut context, std::slice::from_ref(&close_config), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::UsersPending));
+
+    let user_info = user_info_address(&payer, 1);
+    let page = ticket_page_address(&payer, 1, 0);
+    let close_user = close_user_account(&program_id, &payer, &config_address(1), &payer, &user_info, &[]).unwrap();
+    process(&mut context, &[close_user], &[]).await.unwrap();
+    let result = process(&mut context, std::slice::from_ref(&close_config), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::UsersPending));
+    let close_page = close_user_account(&program_id, &payer, &config_address(1), &payer, &user_info, &[page]).unwrap();
+    process(&mut context, &[close_page, close_config], &[]).await.unwrap();
+    assert!(context.banks_client.get_account(user_info).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(page).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(config_address(1)).await.unwrap().is_none());
+
+    // a closed round stays closed
+    let reopen = InitializeRoundBuilder::new(&program_id, &payer, &mint, round_args(&payer, &payer))
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[reopen], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidRound));
+}
+
+#[tokio::test]
+async fn test_next_round_only_opened_by_close() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_round(&mut context, round_args(&payer, &payer)).await;
+    let next_round = |authority: &Pubkey| {
+        let args = ConfigureArgs {
+            round: "2".to_string(),
+            ..round_args(authority, authority)
+        };
+        InitializeRoundBuilder::new(&program_id, authority, &mint, args).instruction().unwrap()
+    };
+
+    // rounds share one counter, so only the upgrade authority opens them outside a close
+    let stranger = Keypair::new();
+    fund(&mut context, &stranger.pubkey()).await;
+    let result = process(&mut context, &[next_round(&stranger.pubkey())], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    // nor past an open round, whose close would find its next config taken
+    let result = process(&mut context, &[next_round(&payer)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RoundOpen));
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap();
+    process(&mut context, &[close], &[]).await.unwrap();
+    let round_data: RoundData = get_state(&mut context, &find_round_address(&program_id).0).await;
+    assert_eq!(round_data.round, 2);
+
+    // the close opened round 2, which blocks a round 3 the same way
+    let args = ConfigureArgs {
+        round: "3".to_string(),
+        ..round_args(&payer, &payer)
+    };
+    let third = InitializeRoundBuilder::new(&program_id, &payer, &mint, args).instruction().unwrap();
+    let result = process(&mut context, &[third], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RoundOpen));
+}
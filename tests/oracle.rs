@@ -0,0 +1,115 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use token_factory::{builder::*, error::AppError, instruction::*, state::*};
+
+#[tokio::test]
+async fn test_oracle_round_needs_oracle_keys() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = create_mint(&mut context).await;
+    let oracle_args = |oracle_program, oracle_authority| ConfigureArgs {
+        randomness_source: RandomnessSource::Oracle,
+        oracle_program,
+        oracle_authority,
+        ..round_args(&payer, &payer)
+    };
+
+    for args in [
+        oracle_args(Pubkey::default(), payer),
+        oracle_args(mock_oracle::id(), Pubkey::default()),
+    ] {
+        let initialize = InitializeRoundBuilder::new(&program_id, &payer, &mint, args).instruction().unwrap();
+        let result = process(&mut context, &[initialize], &[]).await;
+        assert_eq!(app_error(result), Some(AppError::OracleNotSet));
+    }
+    let initialize = InitializeRoundBuilder::new(&program_id, &payer, &mint, oracle_args(mock_oracle::id(), payer))
+        .instruction()
+        .unwrap();
+    process(&mut context, &[initialize], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_round_target_from_oracle() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let oracle_authority = Keypair::new();
+    let mint = start_oracle_round(&mut context, &oracle_authority.pubkey(), 0).await;
+    let randomness = close_with_oracle(&mut context, &oracle_authority.pubkey()).await;
+
+    let consume = consume_randomness(&program_id, &config_address(1), &randomness).unwrap();
+    let result = process(&mut context, std::slice::from_ref(&consume), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RandomnessNotFulfilled));
+
+    process(
+        &mut context,
+        &[
+            mock_oracle::instruction::fulfill(&mock_oracle::id(), &oracle_authority.pubkey(), &randomness, [7; 32])
+                .unwrap(),
+            consume,
+        ],
+        &[&oracle_authority],
+    )
+    .await
+    .unwrap();
+
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert!(config_data.revealed);
+    assert!(config_data.target < 1_000_000);
+    assert_eq!(config_data.randomness_account, randomness);
+
+    let user_info = user_info_address(&payer, 1);
+    let token_account = get_associated_token_address(&payer, &mint);
+    settle_user(&mut context, &payer, 1).await;
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &mint, &spl_token::id()),
+            ClaimBuilder::new(&program_id, &payer, &mint, 1).instruction().unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let user_data: UserData = get_state(&mut context, &user_info).await;
+    assert!(user_data.claimed);
+    assert_eq!(user_data.settled_pages, user_data.pages);
+    let page: TicketPage = get_state(&mut context, &ticket_page_address(&payer, 1, 0)).await;
+    assert_eq!(page.tickets.len(), 2);
+    assert_eq!(token_balance(&mut context, &token_account).await, user_data.reward);
+}
+
+#[tokio::test]
+async fn test_close_rejects_fulfilled_randomness() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_oracle_round(&mut context, &payer, 0).await;
+
+    let randomness = Keypair::new();
+    process(
+        &mut context,
+        &[
+            mock_oracle::instruction::request(&mock_oracle::id(), &payer, &randomness.pubkey(), &payer).unwrap(),
+            mock_oracle::instruction::fulfill(&mock_oracle::id(), &payer, &randomness.pubkey(), [1; 32]).unwrap(),
+        ],
+        &[&randomness],
+    )
+    .await
+    .unwrap();
+
+    let close = CloseBuilder::new(&program_id, &payer, &mint, 1)
+        .randomness(randomness.pubkey())
+        .instruction()
+        .unwrap();
+    let result = process(&mut context, &[close], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RandomnessFulfilled));
+}
@@ -0,0 +1,299 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use token_factory::{builder::*, error::AppError, instruction::*, pda::*, state::*};
+
+#[tokio::test]
+async fn test_buy_splits_revenue() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_oracle_round(&mut context, &payer, 0).await;
+
+    let treasury = Pubkey::new_unique();
+    let partner = Pubkey::new_unique();
+    let mut revenue_shares = [RevenueShare::default(); MAX_REVENUE_RECIPIENTS];
+    revenue_shares[0] = RevenueShare { recipient: treasury, bp: 3000 };
+    revenue_shares[2] = RevenueShare { recipient: partner, bp: 1000 };
+    let update = update_config(
+        &program_id,
+        &payer,
+        &config_address(1),
+        &mint,
+        UpdateConfigArgs {
+            revenue_shares: Some(revenue_shares),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    process(&mut context, &[update], &[]).await.unwrap();
+
+    let buy_with = |recipients: &[Pubkey]| {
+        buy(
+            &program_id,
+            &payer,
+            &config_address(1),
+            &user_info_address(&payer, 1),
+            &payer,
+            &ticket_page_address(&payer, 1, 0),
+            &ticket_page_address(&payer, 1, 1),
+            recipients,
+            false,
+            None,
+            None,
+            BuyTicketsArgs {
+                num: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+    let swapped = buy_with(&[partner, treasury]);
+    assert!(process(&mut context, &[swapped], &[]).await.is_err());
+
+    process(&mut context, &[buy_with(&[treasury, partner])], &[]).await.unwrap();
+    assert_eq!(balance(&mut context, &treasury).await, 30_000_000);
+    assert_eq!(balance(&mut context, &partner).await, 10_000_000);
+}
+
+#[tokio::test]
+async fn test_pot_mode_pays_prizes_in_lamports() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_oracle_round(&mut context, &payer, 5000).await;
+    let (prize_pot, _) = find_prize_pot_address(&program_id);
+
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.pot_collected, 50_000_000);
+
+    let randomness = close_with_oracle(&mut context, &payer).await;
+    process(
+        &mut context,
+        &[
+            mock_oracle::instruction::fulfill(&mock_oracle::id(), &payer, &randomness, [3; 32]).unwrap(),
+            consume_randomness(&program_id, &config_address(1), &randomness).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    settle_user(&mut context, &payer, 1).await;
+
+    // the pot replaces the prefunded reward, the next round starts from an empty pool
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.total_reward, 50_000_000);
+    let next_config_data: ConfigureData = get_state(&mut context, &config_address(2)).await;
+    assert_eq!(next_config_data.total_reward, 0);
+    assert_eq!(next_config_data.pot_bp, 5000);
+
+    let pot_before = balance(&mut context, &prize_pot).await;
+    let claim = claim(
+        &program_id,
+        &payer,
+        &config_address(1),
+        &mint,
+        &user_info_address(&payer, 1),
+        &payer,
+        true,
+        ClaimArgs { round: 1 },
+    )
+    .unwrap();
+    process(&mut context, &[claim], &[]).await.unwrap();
+
+    let user_data: UserData = get_state(&mut context, &user_info_address(&payer, 1)).await;
+    assert!(user_data.claimed);
+    let pot_after = balance(&mut context, &prize_pot).await;
+    assert_eq!(pot_before - pot_after, user_data.reward);
+}
+
+#[tokio::test]
+async fn test_buy_pays_referrer() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_oracle_round(&mut context, &payer, 0).await;
+    let update = update_config(
+        &program_id,
+        &payer,
+        &config_address(1),
+        &mint,
+        UpdateConfigArgs {
+            referral_bp: Some(500),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    process(&mut context, &[update], &[]).await.unwrap();
+
+    let player = Keypair::new();
+    fund(&mut context, &player.pubkey()).await;
+    let referrer_stats = |referrer: &Pubkey| find_referrer_stats_address(&program_id, referrer).0;
+    let buy_referred = |referrer: &Pubkey| {
+        buy(
+            &program_id,
+            &player.pubkey(),
+            &config_address(1),
+            &user_info_address(&player.pubkey(), 1),
+            &payer,
+            &ticket_page_address(&player.pubkey(), 1, 0),
+            &ticket_page_address(&player.pubkey(), 1, 1),
+            &[],
+            false,
+            Some((referrer, &referrer_stats(referrer))),
+            None,
+            BuyTicketsArgs {
+                num: 2,
+                referrer: Some(*referrer),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let self_referral = buy_referred(&player.pubkey());
+    let result = process(&mut context, &[self_referral], &[&player]).await;
+    assert_eq!(app_error(result), Some(AppError::SelfReferral));
+
+    let referrer = Pubkey::new_unique();
+    process(&mut context, &[buy_referred(&referrer)], &[&player]).await.unwrap();
+    assert_eq!(balance(&mut context, &referrer).await, 5_000_000);
+
+    // the referrer is locked in by the first purchase
+    let other = buy_referred(&Pubkey::new_unique());
+    let result = process(&mut context, &[other], &[&player]).await;
+    assert_eq!(app_error(result), Some(AppError::ReferrerLocked));
+
+    let stats: ReferrerStats = get_state(&mut context, &referrer_stats(&referrer)).await;
+    assert_eq!(stats.referrals, 1);
+    assert_eq!(stats.tickets, 2);
+    assert_eq!(stats.lamports_earned, 5_000_000);
+}
+
+#[tokio::test]
+async fn test_buy_paid_in_spl_tokens() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let payment_mint = create_mint(&mut context).await;
+    let payer_account = get_associated_token_address(&payer, &payment_mint);
+    let partner = Pubkey::new_unique();
+    let partner_account = get_associated_token_address(&partner, &payment_mint);
+    process(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &payer, &payment_mint, &spl_token::id()),
+            create_associated_token_account(&payer, &partner, &payment_mint, &spl_token::id()),
+            spl_token::instruction::mint_to(&spl_token::id(), &payment_mint, &payer_account, &payer, &[], 10 * TICKET_PRICE)
+                .unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let charge_addr = Pubkey::new_unique();
+    let mut args = ConfigureArgs {
+        payment_mint: Some(payment_mint),
+        ..round_args(&payer, &charge_addr)
+    };
+    args.revenue_shares[0] = RevenueShare { recipient: partner_account, bp: 2000 };
+    start_round(&mut context, args).await;
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], 2).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+
+    // the share recipients are paid directly, the rest waits in the revenue vault
+    let revenue_vault = find_revenue_vault_address(&program_id, &payment_mint).0;
+    assert_eq!(token_balance(&mut context, &payer_account).await, 8 * TICKET_PRICE);
+    assert_eq!(token_balance(&mut context, &partner_account).await, 2 * TICKET_PRICE / 5);
+    assert_eq!(token_balance(&mut context, &revenue_vault).await, 2 * TICKET_PRICE * 4 / 5);
+    assert_eq!(balance(&mut context, &charge_addr).await, 0);
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.revenue, 2 * TICKET_PRICE * 4 / 5);
+
+    let withdraw = |signer: &Pubkey, amount| {
+        withdraw_revenue(
+            &program_id,
+            signer,
+            &config_address(1),
+            &payment_mint,
+            &payer_account,
+            WithdrawRevenueArgs { amount },
+        )
+        .unwrap()
+    };
+    let stranger = Keypair::new();
+    let result = process(&mut context, &[withdraw(&stranger.pubkey(), 1)], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+    let result = process(&mut context, &[withdraw(&payer, config_data.revenue + 1)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+
+    process(&mut context, &[withdraw(&payer, config_data.revenue)], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut context, &revenue_vault).await, 0);
+    assert_eq!(token_balance(&mut context, &payer_account).await, 8 * TICKET_PRICE + config_data.revenue);
+    let result = process(&mut context, &[withdraw(&payer, 1)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::WithdrawalExceedsAvailable));
+}
+
+#[tokio::test]
+async fn test_pot_surplus_withdrawn_after_settlement() {
+    let mut context = start().await;
+    let program_id = token_factory::id();
+    let payer = context.payer.pubkey();
+    let mint = start_oracle_round(&mut context, &payer, 5000).await;
+    let (prize_pot, _) = find_prize_pot_address(&program_id);
+    let treasury = Pubkey::new_unique();
+    let withdraw = |signer: &Pubkey| withdraw_pot_surplus(&program_id, signer, &config_address(1), &treasury).unwrap();
+
+    let randomness = close_with_oracle(&mut context, &payer).await;
+    let result = process(&mut context, &[withdraw(&payer)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::RoundNotSettled));
+    process(
+        &mut context,
+        &[
+            mock_oracle::instruction::fulfill(&mock_oracle::id(), &payer, &randomness, [3; 32]).unwrap(),
+            consume_randomness(&program_id, &config_address(1), &randomness).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    settle_user(&mut context, &payer, 1).await;
+
+    // rollover is disabled, so the tiers nobody won are owed to no one
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    let surplus = config_data.pot_surplus();
+    assert!(surplus > 0);
+    assert_eq!(surplus, config_data.total_reward - config_data.allocated);
+    let stranger = Keypair::new();
+    let result = process(&mut context, &[withdraw(&stranger.pubkey())], &[&stranger]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidAuthority));
+
+    let claim = ClaimBuilder::new(&program_id, &payer, &mint, 1).pot_mode(true).instruction().unwrap();
+    process(&mut context, &[claim], &[]).await.unwrap();
+    let close_account = close_round_account(&program_id, &payer, &config_address(1)).unwrap();
+    let result = process(&mut context, std::slice::from_ref(&close_account), &[]).await;
+    assert_eq!(app_error(result), Some(AppError::SurplusPending));
+
+    let pot_before = balance(&mut context, &prize_pot).await;
+    process(&mut context, &[withdraw(&payer)], &[]).await.unwrap();
+    assert_eq!(balance(&mut context, &treasury).await, surplus);
+    assert_eq!(balance(&mut context, &prize_pot).await, pot_before - surplus);
+    let result = process(&mut context, &[withdraw(&payer)], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::NoPotSurplus));
+    let close_user = close_user_account(
+        &program_id,
+        &payer,
+        &config_address(1),
+        &payer,
+        &user_info_address(&payer, 1),
+        &[ticket_page_address(&payer, 1, 0)],
+    )
+    .unwrap();
+    process(&mut context, &[close_user, close_account], &[]).await.unwrap();
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use solana_sdk::signature::Signer;
+use std::collections::HashSet;
+use token_factory::{error::AppError, state::*};
+
+#[tokio::test]
+async fn test_quick_picks_in_a_batch_are_distinct() {
+    let mut context = start().await;
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        max_batch: MAX_BATCH_SIZE,
+        ..round_args(&payer, &payer)
+    };
+    start_round(&mut context, args).await;
+
+    let buy = buy_quick_picks(&mut context, &payer, MAX_BATCH_SIZE).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+
+    let page: TicketPage = get_state(&mut context, &ticket_page_address(&payer, 1, 0)).await;
+    assert_eq!(page.tickets.len(), MAX_BATCH_SIZE as usize);
+    assert!(page.tickets.iter().all(|entry| entry.num == 1));
+    let shots: HashSet<[u8; 6]> = page.tickets.iter().map(|entry| entry.shot).collect();
+    assert_eq!(shots.len(), MAX_BATCH_SIZE as usize);
+}
+
+#[tokio::test]
+async fn test_full_page_spills_into_one_new_page() {
+    let mut context = start().await;
+    let payer = context.payer.pubkey();
+    let args = ConfigureArgs {
+        max_batch: MAX_BATCH_SIZE,
+        ..round_args(&payer, &payer)
+    };
+    start_round(&mut context, args).await;
+
+    // (tickets bought, pages afterwards, tickets on the last page)
+    let batches = [(60, 1, 60), (60, 2, 20), (80, 2, 100), (1, 3, 1), (MAX_BATCH_SIZE, 4, 1)];
+    let mut pages = 0;
+    for (num, expected_pages, last_page_len) in batches {
+        let buy = buy_quick_picks(&mut context, &payer, num).await;
+        process(&mut context, &[buy], &[]).await.unwrap();
+        let user_data: UserData = get_state(&mut context, &user_info_address(&payer, 1)).await;
+        assert!(user_data.pages <= pages + 1);
+        assert_eq!(user_data.pages, expected_pages);
+        pages = user_data.pages;
+        let page: TicketPage = get_state(&mut context, &ticket_page_address(&payer, 1, pages - 1)).await;
+        assert_eq!(page.page, pages - 1);
+        assert_eq!(page.tickets.len(), last_page_len);
+    }
+
+    // every earlier page is full and nothing was lost on the way
+    let mut tickets = 0;
+    for page in 0..pages {
+        let page: TicketPage = get_state(&mut context, &ticket_page_address(&payer, 1, page)).await;
+        if page.page < pages - 1 {
+            assert_eq!(page.tickets.len(), TICKETS_PER_PAGE);
+        }
+        tickets += page.tickets.len() as u64;
+    }
+    let user_data: UserData = get_state(&mut context, &user_info_address(&payer, 1)).await;
+    assert_eq!(tickets, user_data.total_shots);
+}
+
+#[tokio::test]
+async fn test_buy_rejects_invalid_tickets() {
+    let mut context = start().await;
+    let payer = context.payer.pubkey();
+    let args = round_args(&payer, &payer);
+    let max_batch = args.max_batch;
+    start_round(&mut context, args).await;
+
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 10], 1).await;
+    let result = process(&mut context, &[buy], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidShot));
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], max_batch + 1).await;
+    let result = process(&mut context, &[buy], &[]).await;
+    assert_eq!(app_error(result), Some(AppError::InvalidTicketNum));
+
+    // neither attempt left a ticket behind, a full batch still goes through
+    let buy = buy_instruction(&mut context, &payer, [1, 2, 3, 4, 5, 6], max_batch).await;
+    process(&mut context, &[buy], &[]).await.unwrap();
+    let config_data: ConfigureData = get_state(&mut context, &config_address(1)).await;
+    assert_eq!(config_data.total_shots, max_batch);
+}